@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use magnus::r_hash::ForEach;
+use magnus::{
+    exception, prelude::*, Error, Float, Integer, RArray, RHash, RString, Ruby, Symbol, Value,
+};
+use minijinja::{Environment, ErrorKind};
+
+use super::error::RbError;
+use super::RbResult;
+
+const TEMPLATE_NAME: &str = "chat";
+
+// renders Jinja chat templates from tokenizer_config.json
+// the environment matches the one used by transformers (trim_blocks, lstrip_blocks,
+// raise_exception, and Python string methods like strip and startswith)
+#[magnus::wrap(class = "Tokenizers::ChatTemplate", frozen_shareable)]
+pub struct RbChatTemplate {
+    env: Environment<'static>,
+    source: String,
+    bos_token: Option<String>,
+    eos_token: Option<String>,
+}
+
+impl RbChatTemplate {
+    pub fn new(source: String, bos_token: Option<String>, eos_token: Option<String>) -> RbResult<Self> {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_unknown_method_callback(minijinja_contrib::pycompat::unknown_method_callback);
+        env.add_function("raise_exception", |message: String| -> Result<String, minijinja::Error> {
+            Err(minijinja::Error::new(ErrorKind::InvalidOperation, message))
+        });
+        env.add_template_owned(TEMPLATE_NAME, source.clone())
+            .map_err(template_error)?;
+
+        Ok(Self {
+            env,
+            source,
+            bos_token,
+            eos_token,
+        })
+    }
+
+    pub fn render(
+        ruby: &Ruby,
+        rb_self: &Self,
+        messages: RArray,
+        add_generation_prompt: bool,
+        kwargs: RHash,
+    ) -> RbResult<String> {
+        let mut context = BTreeMap::new();
+        if let Some(bos_token) = &rb_self.bos_token {
+            context.insert("bos_token".to_string(), minijinja::Value::from(bos_token.as_str()));
+        }
+        if let Some(eos_token) = &rb_self.eos_token {
+            context.insert("eos_token".to_string(), minijinja::Value::from(eos_token.as_str()));
+        }
+        // extra variables like tools or date_string
+        kwargs.foreach(|key: Value, value: Value| {
+            context.insert(template_key(key)?, template_value(ruby, value)?);
+            Ok(ForEach::Continue)
+        })?;
+        context.insert("messages".to_string(), template_value(ruby, messages.as_value())?);
+        context.insert(
+            "add_generation_prompt".to_string(),
+            minijinja::Value::from(add_generation_prompt),
+        );
+
+        rb_self
+            .env
+            .get_template(TEMPLATE_NAME)
+            .and_then(|template| template.render(context))
+            .map_err(template_error)
+    }
+
+    pub fn source(&self) -> String {
+        self.source.clone()
+    }
+
+    pub fn bos_token(&self) -> Option<String> {
+        self.bos_token.clone()
+    }
+
+    pub fn eos_token(&self) -> Option<String> {
+        self.eos_token.clone()
+    }
+}
+
+fn template_error(e: minijinja::Error) -> Error {
+    // use the message from raise_exception as is
+    match (e.kind(), e.detail()) {
+        (ErrorKind::InvalidOperation, Some(detail)) => RbError::from(detail.into()),
+        _ => RbError::from(e.to_string().into()),
+    }
+}
+
+fn template_key(key: Value) -> RbResult<String> {
+    if let Some(sym) = Symbol::from_value(key) {
+        return Ok(sym.name()?.into_owned());
+    }
+    if let Some(s) = RString::from_value(key) {
+        return s.to_string();
+    }
+    Err(Error::new(
+        exception::type_error(),
+        "chat template keys must be strings or symbols",
+    ))
+}
+
+fn template_value(ruby: &Ruby, value: Value) -> RbResult<minijinja::Value> {
+    if value.is_nil() {
+        return Ok(minijinja::Value::from(()));
+    }
+    if value.is_kind_of(ruby.class_true_class()) {
+        return Ok(minijinja::Value::from(true));
+    }
+    if value.is_kind_of(ruby.class_false_class()) {
+        return Ok(minijinja::Value::from(false));
+    }
+    if let Some(s) = RString::from_value(value) {
+        return Ok(minijinja::Value::from(s.to_string()?));
+    }
+    if let Some(sym) = Symbol::from_value(value) {
+        return Ok(minijinja::Value::from(sym.name()?.into_owned()));
+    }
+    if let Some(i) = Integer::from_value(value) {
+        return Ok(minijinja::Value::from(i.to_i64()?));
+    }
+    if let Some(f) = Float::from_value(value) {
+        return Ok(minijinja::Value::from(f.to_f64()));
+    }
+    if let Some(arr) = RArray::from_value(value) {
+        return arr
+            .each()
+            .map(|v| template_value(ruby, v?))
+            .collect::<RbResult<Vec<_>>>()
+            .map(minijinja::Value::from);
+    }
+    if let Some(hash) = RHash::from_value(value) {
+        let mut map = Vec::with_capacity(hash.len());
+        hash.foreach(|key: Value, value: Value| {
+            map.push((template_key(key)?, template_value(ruby, value)?));
+            Ok(ForEach::Continue)
+        })?;
+        return Ok(minijinja::Value::from_iter(map));
+    }
+    Err(Error::new(
+        exception::type_error(),
+        format!("cannot use {} in a chat template", unsafe { value.classname() }),
+    ))
+}
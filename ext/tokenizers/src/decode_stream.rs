@@ -0,0 +1,83 @@
+use std::cell::{RefCell, RefMut};
+
+use super::error::RbError;
+use super::tokenizer::RbTokenizer;
+use super::RbResult;
+
+struct DecodeStreamState {
+    // ids needed to decode the text after the prefix
+    ids: Vec<u32>,
+    // text already returned for ids before prefix_index
+    prefix: String,
+    prefix_index: usize,
+}
+
+// decodes ids one at a time without decoding the whole sequence each step
+// text is only returned once it's complete, so partial UTF-8 sequences from
+// byte fallback or byte-level tokens are held back, and decoders that depend
+// on the previous token (Metaspace, WordPiece) see it through the prefix
+#[magnus::wrap(class = "Tokenizers::DecodeStream")]
+pub struct RbDecodeStream {
+    skip_special_tokens: bool,
+    state: RefCell<DecodeStreamState>,
+}
+
+impl RbDecodeStream {
+    pub fn new(skip_special_tokens: bool) -> Self {
+        Self {
+            skip_special_tokens,
+            state: RefCell::new(DecodeStreamState {
+                ids: Vec::new(),
+                prefix: String::new(),
+                prefix_index: 0,
+            }),
+        }
+    }
+
+    // the state is kept borrowed while decoding, which can release the GVL
+    // and let another thread call the same stream
+    fn state(&self) -> RbResult<RefMut<'_, DecodeStreamState>> {
+        self.state
+            .try_borrow_mut()
+            .map_err(|_| RbError::from("DecodeStream is already in use".into()))
+    }
+
+    pub fn step(&self, tokenizer: &RbTokenizer, id: u32) -> RbResult<Option<String>> {
+        let mut state = self.state()?;
+        state.ids.push(id);
+        let string = tokenizer.decode(state.ids.clone(), self.skip_special_tokens)?;
+        if string.len() <= state.prefix.len() || string.ends_with('\u{FFFD}') {
+            return Ok(None);
+        }
+        if !string.starts_with(&state.prefix) {
+            return Err(RbError::from("Invalid prefix encountered while decoding stream".into()));
+        }
+
+        let new_text = string[state.prefix.len()..].to_string();
+        let new_prefix_index = state.ids.len() - state.prefix_index;
+        let prefix_index = state.prefix_index;
+        state.ids.drain(..prefix_index);
+        state.prefix = tokenizer.decode(state.ids.clone(), self.skip_special_tokens)?;
+        state.prefix_index = new_prefix_index;
+        Ok(Some(new_text))
+    }
+
+    // returns text still held back at the end of the stream, like incomplete UTF-8 sequences,
+    // and resets the stream
+    pub fn flush(&self, tokenizer: &RbTokenizer) -> RbResult<Option<String>> {
+        let mut state = self.state()?;
+        let string = tokenizer.decode(state.ids.clone(), self.skip_special_tokens)?;
+        let new_text = string
+            .strip_prefix(state.prefix.as_str())
+            .ok_or_else(|| RbError::from("Invalid prefix encountered while decoding stream".into()))?
+            .to_string();
+        state.ids.clear();
+        state.prefix.clear();
+        state.prefix_index = 0;
+        Ok(if new_text.is_empty() { None } else { Some(new_text) })
+    }
+
+    pub fn skip_special_tokens(&self) -> bool {
+        self.skip_special_tokens
+    }
+}
@@ -1,9 +1,9 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, PoisonError, RwLock};
 
 use magnus::value::Lazy;
 use magnus::{
-    data_type_builder, function, method, Class, DataType, DataTypeFunctions, Module, Object, RClass, RModule,
-    Ruby, TypedData,
+    data_type_builder, function, method, Class, DataType, DataTypeFunctions, Module, Object, RArray, RClass, RModule,
+    Ruby, TryConvert, TypedData,
 };
 use serde::{Deserialize, Serialize};
 use tk::decoders::bpe::BPEDecoder;
@@ -12,6 +12,7 @@ use tk::decoders::byte_level::ByteLevel;
 use tk::decoders::ctc::CTC;
 use tk::decoders::fuse::Fuse;
 use tk::decoders::metaspace::Metaspace;
+use tk::decoders::sequence::Sequence;
 use tk::decoders::strip::Strip;
 use tk::decoders::wordpiece::WordPiece;
 use tk::decoders::DecoderWrapper;
@@ -62,6 +63,26 @@ macro_rules! setter {
     }};
 }
 impl RbDecoder {
+    pub fn copy(&self) -> RbResult<Self> {
+        deep_copy(self)
+    }
+
+    pub fn dump(&self, _level: i64) -> RbResult<String> {
+        dump(self)
+    }
+
+    pub fn load(json: String) -> RbResult<Self> {
+        load(&json)
+    }
+
+    pub fn decode(&self, tokens: Vec<String>) -> RbResult<String> {
+        self.decoder.decode(tokens).map_err(RbError::from)
+    }
+
+    pub fn decode_chain(&self, tokens: Vec<String>) -> RbResult<Vec<String>> {
+        self.decoder.decode_chain(tokens).map_err(RbError::from)
+    }
+
     pub fn bpe_suffix(&self) -> String {
         getter!(self, BPE, suffix.clone())
     }
@@ -70,6 +91,12 @@ impl RbDecoder {
         setter!(self, BPE, suffix, suffix);
     }
 
+    // returns copies, since changes wouldn't apply to the sequence
+    pub fn sequence_decoders(&self) -> RbResult<Vec<RbDecoder>> {
+        let decoders: Vec<DecoderWrapper> = serialized_field(self, "decoders")?;
+        Ok(decoders.into_iter().map(RbDecoder::from).collect())
+    }
+
     pub fn ctc_cleanup(&self) -> bool {
         getter!(self, CTC, cleanup)
     }
@@ -207,6 +234,23 @@ impl RbReplaceDecoder {
     }
 }
 
+pub struct RbSequenceDecoder {}
+
+impl RbSequenceDecoder {
+    pub fn new(decoders: RArray) -> RbResult<RbDecoder> {
+        let mut sequence = Vec::with_capacity(decoders.len());
+        for d in decoders.each() {
+            let decoder: &RbDecoder = TryConvert::try_convert(d?)?;
+            match &decoder.decoder {
+                RbDecoderWrapper::Wrapped(inner) => {
+                    sequence.push(inner.read().unwrap_or_else(PoisonError::into_inner).clone())
+                }
+            }
+        }
+        Ok(Sequence::new(sequence).into())
+    }
+}
+
 pub struct RbStripDecoder {}
 
 impl RbStripDecoder {
@@ -310,6 +354,11 @@ unsafe impl TypedData for RbDecoder {
             class.undef_default_alloc_func();
             class
         });
+        static SEQUENCE: Lazy<RClass> = Lazy::new(|ruby| {
+            let class: RClass = ruby.get_inner(&DECODERS).const_get("Sequence").unwrap();
+            class.undef_default_alloc_func();
+            class
+        });
         static STRIP: Lazy<RClass> = Lazy::new(|ruby| {
             let class: RClass = ruby.get_inner(&DECODERS).const_get("Strip").unwrap();
             class.undef_default_alloc_func();
@@ -329,9 +378,9 @@ unsafe impl TypedData for RbDecoder {
                 DecoderWrapper::Fuse(_) => ruby.get_inner(&FUSE),
                 DecoderWrapper::Metaspace(_) => ruby.get_inner(&METASPACE),
                 DecoderWrapper::Replace(_) => ruby.get_inner(&REPLACE),
+                DecoderWrapper::Sequence(_) => ruby.get_inner(&SEQUENCE),
                 DecoderWrapper::Strip(_) => ruby.get_inner(&STRIP),
                 DecoderWrapper::WordPiece(_) => ruby.get_inner(&WORD_PIECE),
-                _ => todo!(),
             },
         }
     }
@@ -339,6 +388,11 @@ unsafe impl TypedData for RbDecoder {
 
 pub fn init_decoders(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let decoder = module.define_class("Decoder", ruby.class_object())?;
+    decoder.define_method("_copy", method!(RbDecoder::copy, 0))?;
+    decoder.define_method("_dump", method!(RbDecoder::dump, 1))?;
+    decoder.define_singleton_method("_load", function!(RbDecoder::load, 1))?;
+    decoder.define_method("decode", method!(RbDecoder::decode, 1))?;
+    decoder.define_method("decode_chain", method!(RbDecoder::decode_chain, 1))?;
 
     let class = module.define_class("BPEDecoder", decoder)?;
     class.define_singleton_method("_new", function!(RbBPEDecoder::new, 1))?;
@@ -373,6 +427,10 @@ pub fn init_decoders(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let class = module.define_class("Replace", decoder)?;
     class.define_singleton_method("new", function!(RbReplaceDecoder::new, 2))?;
 
+    let class = module.define_class("Sequence", decoder)?;
+    class.define_singleton_method("new", function!(RbSequenceDecoder::new, 1))?;
+    class.define_method("decoders", method!(RbDecoder::sequence_decoders, 0))?;
+
     let class = module.define_class("Strip", decoder)?;
     class.define_singleton_method("_new", function!(RbStripDecoder::new, 3))?;
     class.define_method("content", method!(RbDecoder::strip_content, 0))?;
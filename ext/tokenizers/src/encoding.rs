@@ -1,5 +1,169 @@
-use magnus::RArray;
-use tk::{Encoding, Offsets};
+use std::collections::HashMap;
+use std::ops::Range;
+
+use magnus::{exception, typed_data::Obj, Error, RArray, RString, TryConvert, Value};
+use tk::{Encoding, Offsets, PaddingDirection, TruncationDirection};
+
+use super::{RbError, RbResult};
+
+// first byte of to_bytes output
+// bump when the layout of tk::Encoding changes
+const ENCODING_FORMAT_VERSION: u8 = 1;
+
+// bytes packed before appending to the output string
+const PACK_CHUNK_SIZE: usize = 8 * 1024;
+
+// element types for packed binary output
+// values are written in native byte order, like Array#pack
+#[derive(Clone, Copy)]
+pub enum PackType {
+    U16,
+    U32,
+    I32,
+    I64,
+}
+
+impl TryConvert for PackType {
+    fn try_convert(ob: Value) -> RbResult<Self> {
+        let value = String::try_convert(ob)?;
+        match value.as_str() {
+            "u16" => Ok(PackType::U16),
+            "u32" => Ok(PackType::U32),
+            "i32" => Ok(PackType::I32),
+            "i64" => Ok(PackType::I64),
+            _ => Err(Error::new(exception::arg_error(), "The type value must be 'u16', 'u32', 'i32', or 'i64'")),
+        }
+    }
+}
+
+impl PackType {
+    fn size(self) -> usize {
+        match self {
+            PackType::U16 => 2,
+            PackType::U32 | PackType::I32 => 4,
+            PackType::I64 => 8,
+        }
+    }
+
+    fn write(self, buf: &mut Vec<u8>, value: i64) -> RbResult<()> {
+        let out_of_range = || Error::new(exception::range_error(), format!("{} is out of range for packed type", value));
+        match self {
+            PackType::U16 => buf.extend_from_slice(&u16::try_from(value).map_err(|_| out_of_range())?.to_ne_bytes()),
+            PackType::U32 => buf.extend_from_slice(&u32::try_from(value).map_err(|_| out_of_range())?.to_ne_bytes()),
+            PackType::I32 => buf.extend_from_slice(&i32::try_from(value).map_err(|_| out_of_range())?.to_ne_bytes()),
+            PackType::I64 => buf.extend_from_slice(&value.to_ne_bytes()),
+        }
+        Ok(())
+    }
+}
+
+// writes packed values into a string allocated at its final size
+// values are appended in chunks, so the output isn't built twice
+struct Packer {
+    pack_type: PackType,
+    out: RString,
+    buf: Vec<u8>,
+}
+
+impl Packer {
+    fn new(pack_type: PackType, len: usize) -> Self {
+        let size = len * pack_type.size();
+        Self {
+            pack_type,
+            out: RString::buf_new(size),
+            buf: Vec::with_capacity(size.min(PACK_CHUNK_SIZE)),
+        }
+    }
+
+    fn push(&mut self, value: i64) -> RbResult<()> {
+        self.pack_type.write(&mut self.buf, value)?;
+        if self.buf.len() >= PACK_CHUNK_SIZE {
+            self.out.cat(&self.buf);
+            self.buf.clear();
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> RString {
+        self.out.cat(&self.buf);
+        self.out
+    }
+}
+
+fn packed_field<'a>(encoding: &'a Encoding, field: &str) -> RbResult<&'a [u32]> {
+    match field {
+        "ids" => Ok(encoding.get_ids()),
+        "type_ids" => Ok(encoding.get_type_ids()),
+        "attention_mask" => Ok(encoding.get_attention_mask()),
+        "special_tokens_mask" => Ok(encoding.get_special_tokens_mask()),
+        _ => Err(Error::new(
+            exception::arg_error(),
+            "The field value must be 'ids', 'type_ids', 'attention_mask', or 'special_tokens_mask'",
+        )),
+    }
+}
+
+fn pack_rows<'a>(
+    encodings: impl Iterator<Item = &'a Encoding>,
+    field: &str,
+    pack_type: PackType,
+    pad_value: i64,
+    length: Option<usize>,
+) -> RbResult<RString> {
+    let rows = encodings
+        .map(|e| packed_field(e, field))
+        .collect::<RbResult<Vec<_>>>()?;
+    let cols = length.unwrap_or_else(|| rows.iter().map(|r| r.len()).max().unwrap_or(0));
+
+    let mut packer = Packer::new(pack_type, rows.len() * cols);
+    for row in rows {
+        if row.len() > cols {
+            return Err(Error::new(
+                exception::arg_error(),
+                format!("Encoding length {} is greater than length {}", row.len(), cols),
+            ));
+        }
+        for v in row {
+            packer.push(*v as i64)?;
+        }
+        for _ in row.len()..cols {
+            packer.push(pad_value)?;
+        }
+    }
+    Ok(packer.finish())
+}
+
+// the inverse of get_sequence_ids
+fn sequence_ranges(sequence_ids: &[Option<usize>]) -> HashMap<usize, Range<usize>> {
+    let mut ranges: HashMap<usize, Range<usize>> = HashMap::new();
+    for (i, id) in sequence_ids.iter().enumerate() {
+        if let Some(id) = id {
+            ranges.entry(*id).and_modify(|r| r.end = i + 1).or_insert(i..i + 1);
+        }
+    }
+    ranges
+}
+
+// copies a range of tokens into a new encoding
+// sequence ids are kept unless sequence_id is given
+fn sub_encoding(encoding: &Encoding, range: Range<usize>, sequence_id: Option<usize>) -> Encoding {
+    let sequence_ranges = match sequence_id {
+        Some(sequence_id) => HashMap::from([(sequence_id, 0..range.len())]),
+        None => sequence_ranges(&encoding.get_sequence_ids()[range.clone()]),
+    };
+
+    Encoding::new(
+        encoding.get_ids()[range.clone()].to_vec(),
+        encoding.get_type_ids()[range.clone()].to_vec(),
+        encoding.get_tokens()[range.clone()].to_vec(),
+        encoding.get_word_ids()[range.clone()].to_vec(),
+        encoding.get_offsets()[range.clone()].to_vec(),
+        encoding.get_special_tokens_mask()[range.clone()].to_vec(),
+        encoding.get_attention_mask()[range].to_vec(),
+        vec![],
+        sequence_ranges,
+    )
+}
 
 #[magnus::wrap(class = "Tokenizers::Encoding")]
 #[repr(transparent)]
@@ -14,6 +178,64 @@ impl From<Encoding> for RbEncoding {
 }
 
 impl RbEncoding {
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_parts(
+        ids: Vec<u32>,
+        type_ids: Vec<u32>,
+        tokens: Vec<String>,
+        word_ids: Vec<Option<u32>>,
+        offsets: Vec<Offsets>,
+        special_tokens_mask: Vec<u32>,
+        attention_mask: Vec<u32>,
+        sequence_ids: Vec<Option<usize>>,
+        overflowing: Vec<Obj<RbEncoding>>,
+    ) -> RbResult<Self> {
+        let len = ids.len();
+        if [
+            type_ids.len(),
+            tokens.len(),
+            word_ids.len(),
+            offsets.len(),
+            special_tokens_mask.len(),
+            attention_mask.len(),
+            sequence_ids.len(),
+        ]
+        .iter()
+        .any(|l| *l != len)
+        {
+            return Err(Error::new(exception::arg_error(), "All fields must have the same length"));
+        }
+
+        Ok(Encoding::new(
+            ids,
+            type_ids,
+            tokens,
+            word_ids,
+            offsets,
+            special_tokens_mask,
+            attention_mask,
+            overflowing.iter().map(|e| e.encoding.clone()).collect(),
+            sequence_ranges(&sequence_ids),
+        )
+        .into())
+    }
+
+    pub fn to_bytes(&self) -> RbResult<RString> {
+        let mut buf = vec![ENCODING_FORMAT_VERSION];
+        bincode::serialize_into(&mut buf, &self.encoding).map_err(|e| RbError::serialization(e.to_string().into()))?;
+        Ok(RString::from_slice(&buf))
+    }
+
+    pub fn from_bytes(bytes: RString) -> RbResult<Self> {
+        let bytes = unsafe { bytes.as_slice() }.to_vec();
+        match bytes.split_first() {
+            Some((&ENCODING_FORMAT_VERSION, data)) => bincode::deserialize::<Encoding>(data)
+                .map(Into::into)
+                .map_err(|e| RbError::serialization(e.to_string().into())),
+            _ => Err(RbError::serialization("Invalid encoding data".into())),
+        }
+    }
+
     pub fn n_sequences(&self) -> usize {
         self.encoding.n_sequences()
     }
@@ -50,6 +272,98 @@ impl RbEncoding {
         self.encoding.get_attention_mask().to_vec()
     }
 
+    pub fn packed(&self, field: String, pack_type: PackType) -> RbResult<RString> {
+        let values = packed_field(&self.encoding, &field)?;
+        let mut packer = Packer::new(pack_type, values.len());
+        for v in values {
+            packer.push(*v as i64)?;
+        }
+        Ok(packer.finish())
+    }
+
+    // packs a row-major matrix with one row per encoding
+    // shorter rows are padded with pad_value
+    pub fn pack_batch(
+        encodings: Vec<Obj<RbEncoding>>,
+        field: String,
+        pack_type: PackType,
+        pad_value: i64,
+        length: Option<usize>,
+    ) -> RbResult<RString> {
+        pack_rows(
+            encodings.iter().map(|e| &e.encoding),
+            &field,
+            pack_type,
+            pad_value,
+            length,
+        )
+    }
+
+    // returns a new encoding, like the other manipulation methods
+    pub fn pad(
+        &self,
+        length: usize,
+        direction: String,
+        pad_id: u32,
+        pad_type_id: u32,
+        pad_token: String,
+    ) -> RbResult<Self> {
+        let direction = match direction.as_str() {
+            "left" => PaddingDirection::Left,
+            "right" => PaddingDirection::Right,
+            _ => return Err(Error::new(exception::arg_error(), "The direction value must be 'left' or 'right'")),
+        };
+        let mut encoding = self.encoding.clone();
+        encoding.pad(length, pad_id, pad_type_id, &pad_token, direction);
+        Ok(encoding.into())
+    }
+
+    pub fn truncate(&self, max_length: usize, stride: usize, direction: String) -> RbResult<Self> {
+        let direction = match direction.as_str() {
+            "left" => TruncationDirection::Left,
+            "right" => TruncationDirection::Right,
+            _ => return Err(Error::new(exception::arg_error(), "The direction value must be 'left' or 'right'")),
+        };
+        // the tokenizers crate panics in this case
+        if max_length > 0 && stride >= max_length && max_length < self.encoding.len() {
+            return Err(Error::new(
+                exception::arg_error(),
+                format!("stride must be less than max_length ({})", max_length),
+            ));
+        }
+        let mut encoding = self.encoding.clone();
+        encoding.truncate(max_length, stride, direction);
+        Ok(encoding.into())
+    }
+
+    pub fn merge(encodings: Vec<Obj<RbEncoding>>, growing_offsets: bool) -> Self {
+        Encoding::merge(encodings.iter().map(|e| e.encoding.clone()), growing_offsets).into()
+    }
+
+    pub fn slice(&self, range: magnus::Range) -> RbResult<Self> {
+        let (start, len) = range.beg_len(self.encoding.len())?;
+        Ok(sub_encoding(&self.encoding, start..start + len, None).into())
+    }
+
+    pub fn sequence(&self, sequence_index: usize) -> Option<Self> {
+        if sequence_index >= self.encoding.n_sequences() {
+            return None;
+        }
+        let positions = self
+            .encoding
+            .get_sequence_ids()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, id)| *id == Some(sequence_index))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let range = match (positions.first(), positions.last()) {
+            (Some(start), Some(end)) => *start..*end + 1,
+            _ => 0..0,
+        };
+        Some(sub_encoding(&self.encoding, range, Some(0)).into())
+    }
+
     pub fn overflowing(&self) -> RArray {
         self.encoding
             .get_overflowing()
@@ -89,3 +403,107 @@ impl RbEncoding {
         self.encoding.char_to_word(char_pos, sequence_index)
     }
 }
+
+// result of encode_batch
+// keeps the encodings in Rust and only creates Encoding objects when rows are accessed
+#[magnus::wrap(class = "Tokenizers::BatchEncoding")]
+pub struct RbBatchEncoding {
+    encodings: Vec<Encoding>,
+    // index of the input each row came from
+    sample_mapping: Vec<usize>,
+}
+
+impl RbBatchEncoding {
+    // with return_overflowing_tokens, each overflowing encoding
+    // gets its own row after the encoding it came from
+    pub fn new(encodings: Vec<Encoding>, return_overflowing_tokens: bool) -> Self {
+        if !return_overflowing_tokens {
+            let sample_mapping = (0..encodings.len()).collect();
+            return Self {
+                encodings,
+                sample_mapping,
+            };
+        }
+
+        let mut rows = Vec::with_capacity(encodings.len());
+        let mut sample_mapping = Vec::with_capacity(encodings.len());
+        for (i, mut encoding) in encodings.into_iter().enumerate() {
+            let overflowing = encoding.take_overflowing();
+            rows.push(encoding);
+            sample_mapping.push(i);
+            for o in overflowing {
+                rows.push(o);
+                sample_mapping.push(i);
+            }
+        }
+        Self {
+            encodings: rows,
+            sample_mapping,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.encodings.len()
+    }
+
+    pub fn get(&self, index: isize) -> Option<RbEncoding> {
+        let index = if index < 0 {
+            index.checked_add(self.encodings.len() as isize)?
+        } else {
+            index
+        };
+        let index = usize::try_from(index).ok()?;
+        self.encodings.get(index).cloned().map(Into::into)
+    }
+
+    pub fn select(&self, rows: Vec<usize>) -> RbResult<Self> {
+        let mut encodings = Vec::with_capacity(rows.len());
+        let mut sample_mapping = Vec::with_capacity(rows.len());
+        for i in rows {
+            let encoding = self
+                .encodings
+                .get(i)
+                .ok_or_else(|| Error::new(exception::index_error(), format!("index {} outside of batch", i)))?;
+            encodings.push(encoding.clone());
+            sample_mapping.push(self.sample_mapping[i]);
+        }
+        Ok(Self {
+            encodings,
+            sample_mapping,
+        })
+    }
+
+    pub fn input_ids(&self) -> Vec<Vec<u32>> {
+        self.encodings.iter().map(|e| e.get_ids().to_vec()).collect()
+    }
+
+    pub fn tokens(&self) -> Vec<Vec<String>> {
+        self.encodings.iter().map(|e| e.get_tokens().to_vec()).collect()
+    }
+
+    pub fn token_type_ids(&self) -> Vec<Vec<u32>> {
+        self.encodings.iter().map(|e| e.get_type_ids().to_vec()).collect()
+    }
+
+    pub fn attention_mask(&self) -> Vec<Vec<u32>> {
+        self.encodings.iter().map(|e| e.get_attention_mask().to_vec()).collect()
+    }
+
+    pub fn special_tokens_mask(&self) -> Vec<Vec<u32>> {
+        self.encodings.iter().map(|e| e.get_special_tokens_mask().to_vec()).collect()
+    }
+
+    pub fn overflow_to_sample_mapping(&self) -> Vec<usize> {
+        self.sample_mapping.clone()
+    }
+
+    pub fn packed(
+        &self,
+        field: String,
+        pack_type: PackType,
+        pad_value: i64,
+        length: Option<usize>,
+    ) -> RbResult<RString> {
+        pack_rows(self.encodings.iter(), &field, pack_type, pad_value, length)
+    }
+}
@@ -1,18 +1,197 @@
-use magnus::{prelude::*, value::Lazy, Error, ExceptionClass, Ruby};
+use std::cell::RefCell;
+use std::fmt;
+use std::path::Path;
 
+use magnus::r_hash::ForEach;
+use magnus::value::{BoxValue, Lazy};
+use magnus::{prelude::*, Error, ExceptionClass, KwArgs, RArray, RHash, Ruby, Symbol, Value};
+
+use super::utils::{in_callback, DisallowedSpecialToken};
 use super::TOKENIZERS;
 
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// Ruby exceptions can't be sent through tokenizers errors,
+// so exceptions from custom components are kept here until converted back
+// the exception is also kept in a BoxValue, since more Ruby code can run
+// before it's converted and the GC can't see thread locals
+thread_local! {
+    static CALLBACK_ERROR: RefCell<Option<(Error, Option<BoxValue<Value>>)>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug)]
+struct CallbackError;
+
+impl fmt::Display for CallbackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error in custom component")
+    }
+}
+
+impl std::error::Error for CallbackError {}
+
 pub struct RbError {}
 
 impl RbError {
     // convert to Error instead of Self
-    pub fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Error {
-        Error::new(error(), e.to_string())
+    pub fn from(e: BoxError) -> Error {
+        convert(e, &ERROR, None)
+    }
+
+    pub fn encoding(e: BoxError) -> Error {
+        convert(e, &ENCODING_ERROR, None)
+    }
+
+    pub fn training(e: BoxError) -> Error {
+        convert(e, &TRAINING_ERROR, None)
+    }
+
+    pub fn serialization(e: BoxError) -> Error {
+        convert(e, &SERIALIZATION_ERROR, None)
+    }
+
+    // for errors reading or writing a file
+    pub fn with_path(e: BoxError, path: &Path) -> Error {
+        convert(e, &ERROR, Some(path))
+    }
+
+    pub fn regex(message: String, pattern: &str) -> Error {
+        let kwargs = RHash::new();
+        if let Err(e) = kwargs.aset(Symbol::new("pattern"), pattern) {
+            return e;
+        }
+        new_error(&REGEX_ERROR, message, Some(kwargs))
+    }
+
+    // for exceptions raised by custom components
+    pub fn callback(e: Error) -> BoxError {
+        let value = e.value().map(BoxValue::new);
+        CALLBACK_ERROR.with(|c| c.replace(Some((e, value))));
+        Box::new(CallbackError)
+    }
+
+    // clears exceptions that weren't converted by an earlier call
+    // only top-level calls clear them, since callbacks can run while one is pending
+    pub fn reset_callback() {
+        if !in_callback() {
+            CALLBACK_ERROR.with(|c| c.take());
+        }
+    }
+
+    // tokenizers ignores errors from normalizers when encoding,
+    // so check if one was raised
+    pub fn check_callback() -> Result<(), BoxError> {
+        if CALLBACK_ERROR.with(|c| c.borrow().is_some()) {
+            return Err(Box::new(CallbackError));
+        }
+        Ok(())
+    }
+
+    // same message as Ruby
+    pub fn unknown_keywords(kwargs: RHash) -> Error {
+        let keywords = RArray::new();
+        let mut names = Vec::new();
+        let res = kwargs.foreach(|key: Value, _: Value| {
+            names.push(key.inspect());
+            keywords.push(key)?;
+            Ok(ForEach::Continue)
+        });
+        if let Err(e) = res {
+            return e;
+        }
+
+        let message = if names.len() == 1 {
+            format!("unknown keyword: {}", names[0])
+        } else {
+            format!("unknown keywords: {}", names.join(", "))
+        };
+        let kwargs = RHash::new();
+        if let Err(e) = kwargs.aset(Symbol::new("keywords"), keywords) {
+            return e;
+        }
+        new_error(&UNKNOWN_KEYWORD_ERROR, message, Some(kwargs))
+    }
+
+    pub fn disallowed_special_token(token: &str, token_id: u32) -> Error {
+        let kwargs = RHash::new();
+        let res = kwargs
+            .aset(Symbol::new("token"), token)
+            .and_then(|_| kwargs.aset(Symbol::new("token_id"), token_id));
+        if let Err(e) = res {
+            return e;
+        }
+        new_error(
+            &DISALLOWED_SPECIAL_TOKEN_ERROR,
+            format!("Encountered disallowed special token: {}", token),
+            Some(kwargs),
+        )
     }
 }
 
-static ERROR: Lazy<ExceptionClass> = Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("Error").unwrap());
+// IO and JSON errors use their own classes regardless of where they happen
+fn convert(e: BoxError, class: &'static Lazy<ExceptionClass>, path: Option<&Path>) -> Error {
+    if e.is::<CallbackError>() {
+        if let Some((e, _)) = CALLBACK_ERROR.with(|c| c.take()) {
+            return e;
+        }
+    }
+
+    if let Some(e) = e.downcast_ref::<DisallowedSpecialToken>() {
+        return RbError::disallowed_special_token(&e.token, e.token_id);
+    }
+
+    let class = if e.is::<std::io::Error>() {
+        &IO_ERROR
+    } else if e.is::<serde_json::Error>() {
+        &SERIALIZATION_ERROR
+    } else {
+        class
+    };
 
-fn error() -> ExceptionClass {
-    Ruby::get().unwrap().get_inner(&ERROR)
+    let kwargs = match path {
+        Some(path) => {
+            let kwargs = RHash::new();
+            if let Err(e) = kwargs.aset(Symbol::new("path"), path.to_string_lossy().into_owned()) {
+                return e;
+            }
+            Some(kwargs)
+        }
+        None => None,
+    };
+    new_error(class, e.to_string(), kwargs)
 }
+
+// falls back to an exception without kwargs, so the original message isn't lost
+fn new_error(class: &'static Lazy<ExceptionClass>, message: String, kwargs: Option<RHash>) -> Error {
+    let class = Ruby::get().unwrap().get_inner(class);
+    let exception = match kwargs {
+        Some(kwargs) => class.new_instance((message.as_str(), KwArgs(kwargs))),
+        None => class.new_instance((message.as_str(),)),
+    };
+    match exception {
+        Ok(exception) => exception.into(),
+        Err(_) => Error::new(class, message),
+    }
+}
+
+static ERROR: Lazy<ExceptionClass> = Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("Error").unwrap());
+
+static DISALLOWED_SPECIAL_TOKEN_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("DisallowedSpecialTokenError").unwrap());
+
+static ENCODING_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("EncodingError").unwrap());
+
+static IO_ERROR: Lazy<ExceptionClass> = Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("IOError").unwrap());
+
+static REGEX_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("RegexError").unwrap());
+
+static SERIALIZATION_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("SerializationError").unwrap());
+
+static TRAINING_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("TrainingError").unwrap());
+
+static UNKNOWN_KEYWORD_ERROR: Lazy<ExceptionClass> =
+    Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("UnknownKeywordError").unwrap());
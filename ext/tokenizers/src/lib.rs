@@ -2,6 +2,8 @@
 
 extern crate tokenizers as tk;
 
+mod chat_template;
+mod decode_stream;
 mod decoders;
 mod encoding;
 mod error;
@@ -13,10 +15,12 @@ mod tokenizer;
 mod trainers;
 mod utils;
 
-use encoding::RbEncoding;
+use chat_template::RbChatTemplate;
+use decode_stream::RbDecodeStream;
+use encoding::{RbBatchEncoding, RbEncoding};
 use error::RbError;
-use tokenizer::RbTokenizer;
-use utils::RbRegex;
+use tokenizer::{RbAddedToken, RbTokenizer};
+use utils::{RbNormalizedString, RbPreTokenizedString, RbRegex};
 
 use magnus::{function, method, prelude::*, value::Lazy, Error, RModule, Ruby};
 
@@ -38,22 +42,38 @@ static TRAINERS: Lazy<RModule> = Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).co
 
 #[magnus::init]
 fn init(ruby: &Ruby) -> RbResult<()> {
+    // allow methods to be called from non-main Ractors
+    unsafe { rb_sys::rb_ext_ractor_safe(true) };
+
     let module = ruby.define_module("Tokenizers")?;
 
     let class = module.define_class("Tokenizer", ruby.class_object())?;
     class.define_singleton_method("new", function!(RbTokenizer::from_model, 1))?;
     class.define_singleton_method("from_file", function!(RbTokenizer::from_file, 1))?;
+    class.define_singleton_method("from_str", function!(RbTokenizer::from_str, 1))?;
+    class.define_singleton_method("from_buffer", function!(RbTokenizer::from_buffer, 1))?;
     class.define_method(
         "add_special_tokens",
         method!(RbTokenizer::add_special_tokens, 1),
     )?;
     class.define_method("train", method!(RbTokenizer::train, 2))?;
+    class.define_method("_train_from_iterator", method!(RbTokenizer::train_from_iterator, 3))?;
     class.define_method("_save", method!(RbTokenizer::save, 2))?;
     class.define_method("add_tokens", method!(RbTokenizer::add_tokens, 1))?;
-    class.define_method("_encode", method!(RbTokenizer::encode, 4))?;
-    class.define_method("_encode_batch", method!(RbTokenizer::encode_batch, 3))?;
+    class.define_method("_encode", method!(RbTokenizer::encode, 7))?;
+    class.define_method("_encode_batch", method!(RbTokenizer::encode_batch, 7))?;
+    class.define_method("_count_tokens", method!(RbTokenizer::count_tokens, 2))?;
+    class.define_method("_count_tokens_batch", method!(RbTokenizer::count_tokens_batch, 2))?;
+    class.define_method("_chunk", method!(RbTokenizer::chunk, 4))?;
+    class.define_method("encode_special_tokens", method!(RbTokenizer::encode_special_tokens, 0))?;
+    class.define_method("encode_special_tokens=", method!(RbTokenizer::set_encode_special_tokens, 1))?;
     class.define_method("_decode", method!(RbTokenizer::decode, 2))?;
     class.define_method("_decode_batch", method!(RbTokenizer::decode_batch, 2))?;
+    class.define_method("model", method!(RbTokenizer::get_model, 0))?;
+    class.define_method("normalizer", method!(RbTokenizer::get_normalizer, 0))?;
+    class.define_method("pre_tokenizer", method!(RbTokenizer::get_pre_tokenizer, 0))?;
+    class.define_method("post_processor", method!(RbTokenizer::get_post_processor, 0))?;
+    class.define_method("decoder", method!(RbTokenizer::get_decoder, 0))?;
     class.define_method("decoder=", method!(RbTokenizer::set_decoder, 1))?;
     class.define_method("pre_tokenizer=", method!(RbTokenizer::set_pre_tokenizer, 1))?;
     class.define_method(
@@ -72,9 +92,19 @@ fn init(ruby: &Ruby) -> RbResult<()> {
     class.define_method("num_special_tokens_to_add", method!(RbTokenizer::num_special_tokens_to_add, 1))?;
     class.define_method("_vocab", method!(RbTokenizer::vocab, 1))?;
     class.define_method("_vocab_size", method!(RbTokenizer::vocab_size, 1))?;
+    class.define_method("added_tokens_decoder", method!(RbTokenizer::added_tokens_decoder, 0))?;
+    class.define_method("special_tokens", method!(RbTokenizer::special_tokens, 0))?;
+    class.define_method("special_token_ids", method!(RbTokenizer::special_token_ids, 0))?;
+    class.define_method("remove_added_tokens", method!(RbTokenizer::remove_added_tokens, 1))?;
+    class.define_method("rename_added_token", method!(RbTokenizer::rename_added_token, 2))?;
     class.define_method("_to_s", method!(RbTokenizer::to_str, 1))?;
+    class.define_method("_copy", method!(RbTokenizer::copy, 0))?;
+    class.define_method("_detach_components", method!(RbTokenizer::detach_components, 0))?;
 
     let class = module.define_class("Encoding", ruby.class_object())?;
+    class.define_singleton_method("_from_parts", function!(RbEncoding::from_parts, 9))?;
+    class.define_singleton_method("from_bytes", function!(RbEncoding::from_bytes, 1))?;
+    class.define_method("to_bytes", method!(RbEncoding::to_bytes, 0))?;
     class.define_method("n_sequences", method!(RbEncoding::n_sequences, 0))?;
     class.define_method("ids", method!(RbEncoding::ids, 0))?;
     class.define_method("tokens", method!(RbEncoding::tokens, 0))?;
@@ -88,6 +118,13 @@ fn init(ruby: &Ruby) -> RbResult<()> {
     )?;
     class.define_method("attention_mask", method!(RbEncoding::attention_mask, 0))?;
     class.define_method("overflowing", method!(RbEncoding::overflowing, 0))?;
+    class.define_method("_packed", method!(RbEncoding::packed, 2))?;
+    class.define_method("_pad", method!(RbEncoding::pad, 5))?;
+    class.define_method("_truncate", method!(RbEncoding::truncate, 3))?;
+    class.define_singleton_method("_merge", function!(RbEncoding::merge, 2))?;
+    class.define_method("slice", method!(RbEncoding::slice, 1))?;
+    class.define_method("sequence", method!(RbEncoding::sequence, 1))?;
+    class.define_singleton_method("_pack_batch", function!(RbEncoding::pack_batch, 5))?;
     class.define_method("_word_to_tokens", method!(RbEncoding::word_to_tokens, 2))?;
     class.define_method("_word_to_chars", method!(RbEncoding::word_to_chars, 2))?;
     class.define_method(
@@ -99,8 +136,76 @@ fn init(ruby: &Ruby) -> RbResult<()> {
     class.define_method("_char_to_token", method!(RbEncoding::char_to_token, 2))?;
     class.define_method("_char_to_word", method!(RbEncoding::char_to_word, 2))?;
 
+    let class = module.define_class("BatchEncoding", ruby.class_object())?;
+    class.define_method("size", method!(RbBatchEncoding::size, 0))?;
+    class.define_method("_get", method!(RbBatchEncoding::get, 1))?;
+    class.define_method("_select", method!(RbBatchEncoding::select, 1))?;
+    class.define_method("input_ids", method!(RbBatchEncoding::input_ids, 0))?;
+    class.define_method("tokens", method!(RbBatchEncoding::tokens, 0))?;
+    class.define_method("token_type_ids", method!(RbBatchEncoding::token_type_ids, 0))?;
+    class.define_method("attention_mask", method!(RbBatchEncoding::attention_mask, 0))?;
+    class.define_method(
+        "special_tokens_mask",
+        method!(RbBatchEncoding::special_tokens_mask, 0),
+    )?;
+    class.define_method(
+        "overflow_to_sample_mapping",
+        method!(RbBatchEncoding::overflow_to_sample_mapping, 0),
+    )?;
+    class.define_method("_packed", method!(RbBatchEncoding::packed, 4))?;
+
+    let class = module.define_class("AddedToken", ruby.class_object())?;
+    class.define_singleton_method("_new", function!(RbAddedToken::new, 2))?;
+    class.define_method("content", method!(RbAddedToken::get_content, 0))?;
+    class.define_method("special", method!(RbAddedToken::get_special, 0))?;
+    class.define_method("single_word", method!(RbAddedToken::get_single_word, 0))?;
+    class.define_method("lstrip", method!(RbAddedToken::get_lstrip, 0))?;
+    class.define_method("rstrip", method!(RbAddedToken::get_rstrip, 0))?;
+    class.define_method("normalized", method!(RbAddedToken::get_normalized, 0))?;
+    class.define_method("to_s", method!(RbAddedToken::get_content, 0))?;
+
+    let class = module.define_class("ChatTemplate", ruby.class_object())?;
+    class.define_singleton_method("_new", function!(RbChatTemplate::new, 3))?;
+    class.define_method("_render", method!(RbChatTemplate::render, 3))?;
+    class.define_method("source", method!(RbChatTemplate::source, 0))?;
+    class.define_method("bos_token", method!(RbChatTemplate::bos_token, 0))?;
+    class.define_method("eos_token", method!(RbChatTemplate::eos_token, 0))?;
+
+    let class = module.define_class("DecodeStream", ruby.class_object())?;
+    class.define_singleton_method("_new", function!(RbDecodeStream::new, 1))?;
+    class.define_method("_step", method!(RbDecodeStream::step, 2))?;
+    class.define_method("_flush", method!(RbDecodeStream::flush, 1))?;
+    class.define_method("skip_special_tokens", method!(RbDecodeStream::skip_special_tokens, 0))?;
+
     let class = module.define_class("Regex", ruby.class_object())?;
     class.define_singleton_method("new", function!(RbRegex::new, 1))?;
+    class.define_method("source", method!(RbRegex::source, 0))?;
+
+    let class = module.define_class("NormalizedString", ruby.class_object())?;
+    class.define_method("normalized", method!(RbNormalizedString::normalized, 0))?;
+    class.define_method("original", method!(RbNormalizedString::original, 0))?;
+    class.define_method("nfd", method!(RbNormalizedString::nfd, 0))?;
+    class.define_method("nfkd", method!(RbNormalizedString::nfkd, 0))?;
+    class.define_method("nfc", method!(RbNormalizedString::nfc, 0))?;
+    class.define_method("nfkc", method!(RbNormalizedString::nfkc, 0))?;
+    class.define_method("lowercase", method!(RbNormalizedString::lowercase, 0))?;
+    class.define_method("uppercase", method!(RbNormalizedString::uppercase, 0))?;
+    class.define_method("prepend", method!(RbNormalizedString::prepend, 1))?;
+    class.define_method("append", method!(RbNormalizedString::append, 1))?;
+    class.define_method("lstrip", method!(RbNormalizedString::lstrip, 0))?;
+    class.define_method("rstrip", method!(RbNormalizedString::rstrip, 0))?;
+    class.define_method("strip", method!(RbNormalizedString::strip, 0))?;
+    class.define_method("clear", method!(RbNormalizedString::clear, 0))?;
+    class.define_method("replace", method!(RbNormalizedString::replace, 2))?;
+    class.define_method("split", method!(RbNormalizedString::split, 2))?;
+    class.define_method("slice", method!(RbNormalizedString::slice, 1))?;
+    class.define_method("filter", method!(RbNormalizedString::filter, 0))?;
+    class.define_method("map", method!(RbNormalizedString::map, 0))?;
+
+    let class = module.define_class("PreTokenizedString", ruby.class_object())?;
+    class.define_method("split", method!(RbPreTokenizedString::split, 0))?;
+    class.define_method("_get_splits", method!(RbPreTokenizedString::get_splits, 2))?;
+    class.define_method("tokenize", method!(RbPreTokenizedString::tokenize, 0))?;
 
     let models = module.define_module("Models")?;
     let pre_tokenizers = module.define_module("PreTokenizers")?;
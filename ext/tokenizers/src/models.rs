@@ -16,6 +16,7 @@ use tk::models::wordlevel::WordLevel;
 use tk::models::wordpiece::{WordPiece, WordPieceBuilder};
 use tk::{Model, Token};
 
+use super::utils::{deep_copy, dump, load};
 use super::{MODELS, RbError, RbResult};
 
 #[derive(DataTypeFunctions, Clone, Serialize, Deserialize)]
@@ -107,8 +108,7 @@ impl RbBPE {
         }
 
         if !kwargs.is_empty() {
-            // TODO improve message
-            return Err(Error::new(exception::arg_error(), "unknown keyword"));
+            return Err(RbError::unknown_keywords(kwargs));
         }
 
         builder.build().map(|v| v.into()).map_err(RbError::from)
@@ -123,7 +123,11 @@ impl RbBPE {
     }
 
     pub fn from_file(vocab: String, merges: String, kwargs: RHash) -> RbResult<RbModel> {
-        let (vocab, merges) = BPE::read_file(&vocab, &merges).map_err(RbError::from)?;
+        let (vocab, merges) = BPE::read_file(&vocab, &merges).map_err(|e| {
+            // the vocab file is read first, so the merges file caused the error if the vocab can be read
+            let path = if WordLevel::read_file(&vocab).is_ok() { &merges } else { &vocab };
+            RbError::with_path(e, Path::new(path))
+        })?;
 
         RbBPE::new(Some(vocab), Some(merges), kwargs)
     }
@@ -150,6 +154,18 @@ macro_rules! setter {
 }
 
 impl RbModel {
+    pub fn copy(&self) -> RbResult<Self> {
+        deep_copy(self)
+    }
+
+    pub fn dump(&self, _level: i64) -> RbResult<String> {
+        dump(self)
+    }
+
+    pub fn load(json: String) -> RbResult<Self> {
+        load(&json)
+    }
+
     pub fn bpe_dropout(&self) -> Option<f32> {
         getter!(self, BPE, dropout)
     }
@@ -261,11 +277,11 @@ impl RbWordLevel {
     }
 
     pub fn read_file(vocab: String) -> RbResult<Vocab> {
-        WordLevel::read_file(&vocab).map_err(RbError::from)
+        WordLevel::read_file(&vocab).map_err(|e| RbError::with_path(e, Path::new(&vocab)))
     }
 
     pub fn from_file(vocab: String, unk_token: Option<String>) -> RbResult<RbModel> {
-        let vocab = WordLevel::read_file(&vocab).map_err(RbError::from)?;
+        let vocab = WordLevel::read_file(&vocab).map_err(|e| RbError::with_path(e, Path::new(&vocab)))?;
 
         RbWordLevel::new(Some(vocab), unk_token)
     }
@@ -291,8 +307,7 @@ impl RbWordPiece {
         }
 
         if !kwargs.is_empty() {
-            // TODO improve message
-            return Err(Error::new(exception::arg_error(), "unknown keyword"));
+            return Err(RbError::unknown_keywords(kwargs));
         }
 
         builder.build().map(|v| v.into()).map_err(RbError::from)
@@ -307,7 +322,7 @@ impl RbWordPiece {
     }
 
     pub fn from_file(vocab: String, kwargs: RHash) -> RbResult<RbModel> {
-        let vocab = WordPiece::read_file(&vocab).map_err(RbError::from)?;
+        let vocab = WordPiece::read_file(&vocab).map_err(|e| RbError::with_path(e, Path::new(&vocab)))?;
 
         RbWordPiece::new(Some(vocab), kwargs)
     }
@@ -360,6 +375,9 @@ unsafe impl TypedData for RbModel {
 
 pub fn init_models(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let model = module.define_class("Model", ruby.class_object())?;
+    model.define_method("_copy", method!(RbModel::copy, 0))?;
+    model.define_method("_dump", method!(RbModel::dump, 1))?;
+    model.define_singleton_method("_load", function!(RbModel::load, 1))?;
 
     let class = module.define_class("BPE", model)?;
     class.define_singleton_method("_new", function!(RbBPE::new, 3))?;
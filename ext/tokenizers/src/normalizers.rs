@@ -1,13 +1,14 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, PoisonError, RwLock};
 
 use magnus::{
     data_type_builder, function, method, value::Lazy, Class, DataType, DataTypeFunctions, Module, Object, RArray, RClass, RModule,
-    Ruby, TryConvert, TypedData,
+    RString, Ruby, TryConvert, TypedData, Value,
 };
-use serde::ser::SerializeStruct;
-use serde::{Deserialize, Serialize, Serializer};
+use serde::de::Error as DeError;
+use serde::ser::{Error as SerError, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tk::normalizers::{
-    BertNormalizer, Lowercase, Nmt, NormalizerWrapper, Replace, Prepend, Strip, StripAccents,
+    BertNormalizer, Lowercase, Nmt, NormalizerWrapper, Precompiled, Replace, Prepend, Strip, StripAccents,
     NFC, NFD, NFKC, NFKD,
 };
 use tk::{NormalizedString, Normalizer};
@@ -22,15 +23,39 @@ pub struct RbNormalizer {
 }
 
 impl RbNormalizer {
+    pub fn copy(&self) -> RbResult<Self> {
+        deep_copy(self)
+    }
+
+    pub fn dump(&self, _level: i64) -> RbResult<String> {
+        dump(self)
+    }
+
+    pub fn load(json: String) -> RbResult<Self> {
+        load(&json)
+    }
+
     pub(crate) fn new(normalizer: RbNormalizerTypeWrapper) -> Self {
         RbNormalizer { normalizer }
     }
 
     pub fn normalize_str(&self, sequence: String) -> RbResult<String> {
+        RbError::reset_callback();
         let mut normalized = NormalizedString::from(sequence);
         self.normalizer.normalize(&mut normalized).map_err(RbError::from)?;
         Ok(normalized.get().to_owned())
     }
+
+    // checked by the tokenizer before releasing the GVL
+    // poisoned locks are recovered like the tokenizer lock, since this is called on every encode
+    pub(crate) fn is_custom(&self) -> bool {
+        match &self.normalizer {
+            RbNormalizerTypeWrapper::Sequence(seq) => seq
+                .iter()
+                .any(|n| n.read().unwrap_or_else(PoisonError::into_inner).is_custom()),
+            RbNormalizerTypeWrapper::Single(inner) => inner.read().unwrap_or_else(PoisonError::into_inner).is_custom(),
+        }
+    }
 }
 
 impl Normalizer for RbNormalizer {
@@ -112,6 +137,21 @@ impl RbNormalizer {
         setter!(self, Prepend, prepend, prepend)
     }
 
+    fn replace_pattern(&self) -> RbResult<Value> {
+        serialized_field::<_, SerializedPattern>(self, "pattern")?.into_ruby()
+    }
+
+    fn replace_content(&self) -> RbResult<String> {
+        serialized_field(self, "content")
+    }
+
+    // stored as base64 when serialized
+    fn precompiled_charsmap(&self) -> RbResult<RString> {
+        let charsmap: String = serialized_field(self, "precompiled_charsmap")?;
+        let bytes = base64::decode(charsmap).map_err(|e| RbError::serialization(e.into()))?;
+        Ok(RString::from_slice(&bytes))
+    }
+
     fn strip_left(&self) -> bool {
         getter!(self, StripNormalizer, strip_left)
     }
@@ -185,6 +225,17 @@ impl RbNmt {
     }
 }
 
+pub struct RbPrecompiled {}
+
+impl RbPrecompiled {
+    pub fn new(precompiled_charsmap: RString) -> RbResult<RbNormalizer> {
+        let bytes = unsafe { precompiled_charsmap.as_slice() };
+        Precompiled::from(bytes)
+            .map(|v| v.into())
+            .map_err(|e| RbError::from(e.into()))
+    }
+}
+
 pub struct RbReplace {}
 
 impl RbReplace {
@@ -217,6 +268,15 @@ impl RbStripAccents {
     }
 }
 
+pub struct RbCustomNormalizer {}
+
+impl RbCustomNormalizer {
+    pub fn new(normalizer: Value) -> RbResult<RbNormalizer> {
+        let custom = CustomNormalizer::new(normalizer)?;
+        Ok(RbNormalizer::new(RbNormalizerWrapper::Custom(custom).into()))
+    }
+}
+
 pub struct RbSequence {}
 
 impl RbSequence {
@@ -233,13 +293,59 @@ impl RbSequence {
     }
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct CustomNormalizer {
+    inner: RbCustomObject,
+}
+
+impl CustomNormalizer {
+    pub fn new(inner: Value) -> RbResult<Self> {
+        Ok(Self {
+            inner: RbCustomObject::new(inner)?,
+        })
+    }
+}
+
+impl Normalizer for CustomNormalizer {
+    // the NormalizedString passed to Ruby is invalidated when the guard is dropped
+    fn normalize(&self, normalized: &mut NormalizedString) -> tk::Result<()> {
+        let guard = RefMutGuard::new(normalized);
+        self.inner.call("normalize", (RbNormalizedString::from(guard.get()),))?;
+        Ok(())
+    }
+}
+
+impl Serialize for CustomNormalizer {
+    fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Err(S::Error::custom("Custom Normalizer cannot be serialized"))
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomNormalizer {
+    fn deserialize<D>(_deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Err(D::Error::custom("Custom Normalizer cannot be deserialized"))
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub(crate) enum RbNormalizerWrapper {
-    // Custom(CustomNormalizer),
+    Custom(CustomNormalizer),
     Wrapped(NormalizerWrapper),
 }
 
+impl RbNormalizerWrapper {
+    fn is_custom(&self) -> bool {
+        matches!(self, RbNormalizerWrapper::Custom(_))
+    }
+}
+
 impl Serialize for RbNormalizerWrapper {
     fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
     where
@@ -247,7 +353,7 @@ impl Serialize for RbNormalizerWrapper {
     {
         match self {
             RbNormalizerWrapper::Wrapped(inner) => inner.serialize(serializer),
-            // RbNormalizerWrapper::Custom(inner) => inner.serialize(serializer),
+            RbNormalizerWrapper::Custom(inner) => inner.serialize(serializer),
         }
     }
 }
@@ -320,7 +426,7 @@ impl Normalizer for RbNormalizerWrapper {
     fn normalize(&self, normalized: &mut NormalizedString) -> tk::Result<()> {
         match self {
             RbNormalizerWrapper::Wrapped(inner) => inner.normalize(normalized),
-            // RbNormalizerWrapper::Custom(inner) => inner.normalize(normalized),
+            RbNormalizerWrapper::Custom(inner) => inner.normalize(normalized),
         }
     }
 }
@@ -346,6 +452,11 @@ unsafe impl TypedData for RbNormalizer {
             class.undef_default_alloc_func();
             class
         });
+        static CUSTOM: Lazy<RClass> = Lazy::new(|ruby| {
+            let class: RClass = ruby.get_inner(&NORMALIZERS).const_get("Custom").unwrap();
+            class.undef_default_alloc_func();
+            class
+        });
         static BERT_NORMALIZER: Lazy<RClass> = Lazy::new(|ruby| {
             let class: RClass = ruby.get_inner(&NORMALIZERS).const_get("BertNormalizer").unwrap();
             class.undef_default_alloc_func();
@@ -381,6 +492,11 @@ unsafe impl TypedData for RbNormalizer {
             class.undef_default_alloc_func();
             class
         });
+        static PRECOMPILED: Lazy<RClass> = Lazy::new(|ruby| {
+            let class: RClass = ruby.get_inner(&NORMALIZERS).const_get("Precompiled").unwrap();
+            class.undef_default_alloc_func();
+            class
+        });
         static REPLACE: Lazy<RClass> = Lazy::new(|ruby| {
             let class: RClass = ruby.get_inner(&NORMALIZERS).const_get("Replace").unwrap();
             class.undef_default_alloc_func();
@@ -404,6 +520,7 @@ unsafe impl TypedData for RbNormalizer {
         match &value.normalizer {
             RbNormalizerTypeWrapper::Sequence(_seq) => ruby.get_inner(&SEQUENCE),
             RbNormalizerTypeWrapper::Single(inner) => match &*inner.read().unwrap() {
+                RbNormalizerWrapper::Custom(_) => ruby.get_inner(&CUSTOM),
                 RbNormalizerWrapper::Wrapped(wrapped) => match &wrapped {
                     NormalizerWrapper::BertNormalizer(_) => ruby.get_inner(&BERT_NORMALIZER),
                     NormalizerWrapper::Lowercase(_) => ruby.get_inner(&LOWERCASE),
@@ -412,11 +529,12 @@ unsafe impl TypedData for RbNormalizer {
                     NormalizerWrapper::NFKC(_) => ruby.get_inner(&NFKC),
                     NormalizerWrapper::NFKD(_) => ruby.get_inner(&NFKD),
                     NormalizerWrapper::Nmt(_) => ruby.get_inner(&NMT),
+                    NormalizerWrapper::Precompiled(_) => ruby.get_inner(&PRECOMPILED),
                     NormalizerWrapper::Replace(_) => ruby.get_inner(&REPLACE),
                     NormalizerWrapper::Prepend(_) => ruby.get_inner(&PREPEND),
                     NormalizerWrapper::StripNormalizer(_) => ruby.get_inner(&STRIP),
                     NormalizerWrapper::StripAccents(_) => ruby.get_inner(&STRIP_ACCENTS),
-                    _ => todo!(),
+                    NormalizerWrapper::Sequence(_) => ruby.get_inner(&SEQUENCE),
                 },
             },
         }
@@ -425,11 +543,17 @@ unsafe impl TypedData for RbNormalizer {
 
 pub fn init_normalizers(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let normalizer = module.define_class("Normalizer", ruby.class_object())?;
+    normalizer.define_method("_copy", method!(RbNormalizer::copy, 0))?;
+    normalizer.define_method("_dump", method!(RbNormalizer::dump, 1))?;
+    normalizer.define_singleton_method("_load", function!(RbNormalizer::load, 1))?;
     normalizer.define_method("normalize_str", method!(RbNormalizer::normalize_str, 1))?;
 
     let class = module.define_class("Sequence", normalizer)?;
     class.define_singleton_method("new", function!(RbSequence::new, 1))?;
 
+    let class = module.define_class("Custom", normalizer)?;
+    class.define_singleton_method("new", function!(RbCustomNormalizer::new, 1))?;
+
     let class = module.define_class("BertNormalizer", normalizer)?;
     class.define_singleton_method("_new", function!(RbBertNormalizer::new, 4))?;
     class.define_method("clean_text", method!(RbNormalizer::bert_clean_text, 0))?;
@@ -459,8 +583,14 @@ pub fn init_normalizers(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let class = module.define_class("Nmt", normalizer)?;
     class.define_singleton_method("new", function!(RbNmt::new, 0))?;
 
+    let class = module.define_class("Precompiled", normalizer)?;
+    class.define_singleton_method("new", function!(RbPrecompiled::new, 1))?;
+    class.define_method("precompiled_charsmap", method!(RbNormalizer::precompiled_charsmap, 0))?;
+
     let class = module.define_class("Replace", normalizer)?;
     class.define_singleton_method("new", function!(RbReplace::new, 2))?;
+    class.define_method("pattern", method!(RbNormalizer::replace_pattern, 0))?;
+    class.define_method("content", method!(RbNormalizer::replace_content, 0))?;
 
     let class = module.define_class("Prepend", normalizer)?;
     class.define_singleton_method("_new", function!(RbPrepend::new, 1))?;
@@ -1,12 +1,13 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, PoisonError, RwLock};
 
 use magnus::{
     data_type_builder, function, method, value::Lazy, Class, DataType, DataTypeFunctions, Module, Object,
-    RArray, RClass, RModule, Ruby, TryConvert, TypedData,
+    RArray, RClass, RModule, Ruby, TryConvert, TypedData, Value,
 };
 
-use serde::ser::SerializeStruct;
-use serde::{Deserialize, Serialize, Serializer};
+use serde::de::Error as DeError;
+use serde::ser::{Error as SerError, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use tk::pre_tokenizers::bert::BertPreTokenizer;
 use tk::pre_tokenizers::byte_level::ByteLevel;
@@ -31,7 +32,20 @@ pub struct RbPreTokenizer {
 }
 
 impl RbPreTokenizer {
+    pub fn copy(&self) -> RbResult<Self> {
+        deep_copy(self)
+    }
+
+    pub fn dump(&self, _level: i64) -> RbResult<String> {
+        dump(self)
+    }
+
+    pub fn load(json: String) -> RbResult<Self> {
+        load(&json)
+    }
+
     fn pre_tokenize_str(&self, s: String) -> RbResult<Vec<(String, Offsets)>> {
+        RbError::reset_callback();
         let mut pretokenized = tk::tokenizer::PreTokenizedString::from(s);
 
         self.pretok.pre_tokenize(&mut pretokenized).map_err(RbError::from)?;
@@ -42,6 +56,17 @@ impl RbPreTokenizer {
             .map(|(s, o, _)| (s.to_owned(), o))
             .collect())
     }
+
+    // checked by the tokenizer before releasing the GVL
+    // poisoned locks are recovered like the tokenizer lock, since this is called on every encode
+    pub(crate) fn is_custom(&self) -> bool {
+        match &self.pretok {
+            RbPreTokenizerTypeWrapper::Sequence(seq) => seq
+                .iter()
+                .any(|p| p.read().unwrap_or_else(PoisonError::into_inner).is_custom()),
+            RbPreTokenizerTypeWrapper::Single(inner) => inner.read().unwrap_or_else(PoisonError::into_inner).is_custom(),
+        }
+    }
 }
 
 macro_rules! getter {
@@ -110,6 +135,19 @@ impl RbPreTokenizer {
         setter!(self, Delimiter, delimiter, delimiter);
     }
 
+    fn split_pattern(&self) -> RbResult<Value> {
+        serialized_field::<_, SerializedPattern>(self, "pattern")?.into_ruby()
+    }
+
+    fn split_behavior(&self) -> RbResult<&'static str> {
+        let behavior = serialized_field(self, "behavior")?;
+        Ok(RbSplitDelimiterBehavior(behavior).name())
+    }
+
+    fn split_invert(&self) -> RbResult<bool> {
+        serialized_field(self, "invert")
+    }
+
     fn digits_individual_digits(&self) -> bool {
         getter!(self, Digits, individual_digits)
     }
@@ -234,6 +272,15 @@ impl RbBertPreTokenizer {
     }
 }
 
+pub struct RbCustomPreTokenizer {}
+
+impl RbCustomPreTokenizer {
+    pub fn new(pretok: Value) -> RbResult<RbPreTokenizer> {
+        let custom = CustomPreTokenizer::new(pretok)?;
+        Ok(RbPreTokenizer::new(RbPreTokenizerWrapper::Custom(custom).into()))
+    }
+}
+
 pub struct RbSequence {}
 
 impl RbSequence {
@@ -252,13 +299,59 @@ impl RbSequence {
     }
 }
 
+#[derive(Clone)]
+pub(crate) struct CustomPreTokenizer {
+    inner: RbCustomObject,
+}
+
+impl CustomPreTokenizer {
+    pub fn new(inner: Value) -> RbResult<Self> {
+        Ok(Self {
+            inner: RbCustomObject::new(inner)?,
+        })
+    }
+}
+
+impl PreTokenizer for CustomPreTokenizer {
+    // the PreTokenizedString passed to Ruby is invalidated when the guard is dropped
+    fn pre_tokenize(&self, sentence: &mut PreTokenizedString) -> tk::Result<()> {
+        let guard = RefMutGuard::new(sentence);
+        self.inner.call("pre_tokenize", (RbPreTokenizedString::from(guard.get()),))?;
+        Ok(())
+    }
+}
+
+impl Serialize for CustomPreTokenizer {
+    fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Err(S::Error::custom("Custom PreTokenizer cannot be serialized"))
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomPreTokenizer {
+    fn deserialize<D>(_deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Err(D::Error::custom("Custom PreTokenizer cannot be deserialized"))
+    }
+}
+
 #[derive(Clone, Deserialize)]
 #[serde(untagged)]
 pub(crate) enum RbPreTokenizerWrapper {
-    // Custom(CustomPreTokenizer),
+    Custom(CustomPreTokenizer),
     Wrapped(PreTokenizerWrapper),
 }
 
+impl RbPreTokenizerWrapper {
+    fn is_custom(&self) -> bool {
+        matches!(self, RbPreTokenizerWrapper::Custom(_))
+    }
+}
+
 impl Serialize for RbPreTokenizerWrapper {
     fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
     where
@@ -266,7 +359,7 @@ impl Serialize for RbPreTokenizerWrapper {
     {
         match self {
             RbPreTokenizerWrapper::Wrapped(inner) => inner.serialize(serializer),
-            // RbPreTokenizerWrapper::Custom(inner) => inner.serialize(serializer),
+            RbPreTokenizerWrapper::Custom(inner) => inner.serialize(serializer),
         }
     }
 }
@@ -339,7 +432,7 @@ impl PreTokenizer for RbPreTokenizerWrapper {
     fn pre_tokenize(&self, pretok: &mut PreTokenizedString) -> tk::Result<()> {
         match self {
             RbPreTokenizerWrapper::Wrapped(inner) => inner.pre_tokenize(pretok),
-            // RbPreTokenizerWrapper::Custom(inner) => inner.pre_tokenize(pretok),
+            RbPreTokenizerWrapper::Custom(inner) => inner.pre_tokenize(pretok),
         }
     }
 }
@@ -365,6 +458,11 @@ unsafe impl TypedData for RbPreTokenizer {
             class.undef_default_alloc_func();
             class
         });
+        static CUSTOM: Lazy<RClass> = Lazy::new(|ruby| {
+            let class: RClass = ruby.get_inner(&PRE_TOKENIZERS).const_get("Custom").unwrap();
+            class.undef_default_alloc_func();
+            class
+        });
         static BERT_PRE_TOKENIZER: Lazy<RClass> = Lazy::new(|ruby| {
             let class: RClass = ruby.get_inner(&PRE_TOKENIZERS).const_get("BertPreTokenizer").unwrap();
             class.undef_default_alloc_func();
@@ -418,6 +516,7 @@ unsafe impl TypedData for RbPreTokenizer {
         match &value.pretok {
             RbPreTokenizerTypeWrapper::Sequence(_seq) => ruby.get_inner(&SEQUENCE),
             RbPreTokenizerTypeWrapper::Single(inner) => match &*inner.read().unwrap() {
+                RbPreTokenizerWrapper::Custom(_) => ruby.get_inner(&CUSTOM),
                 RbPreTokenizerWrapper::Wrapped(wrapped) => match &wrapped {
                     PreTokenizerWrapper::BertPreTokenizer(_) => ruby.get_inner(&BERT_PRE_TOKENIZER),
                     PreTokenizerWrapper::ByteLevel(_) => ruby.get_inner(&BYTE_LEVEL),
@@ -429,7 +528,7 @@ unsafe impl TypedData for RbPreTokenizer {
                     PreTokenizerWrapper::UnicodeScripts(_) => ruby.get_inner(&UNICODE_SCRIPTS),
                     PreTokenizerWrapper::Whitespace(_) => ruby.get_inner(&WHITESPACE),
                     PreTokenizerWrapper::WhitespaceSplit(_) => ruby.get_inner(&WHITESPACE_SPLIT),
-                    _ => todo!(),
+                    PreTokenizerWrapper::Sequence(_) => ruby.get_inner(&SEQUENCE),
                 },
             },
         }
@@ -438,11 +537,17 @@ unsafe impl TypedData for RbPreTokenizer {
 
 pub fn init_pre_tokenizers(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let pre_tokenizer = module.define_class("PreTokenizer", ruby.class_object())?;
+    pre_tokenizer.define_method("_copy", method!(RbPreTokenizer::copy, 0))?;
+    pre_tokenizer.define_method("_dump", method!(RbPreTokenizer::dump, 1))?;
+    pre_tokenizer.define_singleton_method("_load", function!(RbPreTokenizer::load, 1))?;
     pre_tokenizer.define_method("pre_tokenize_str", method!(RbPreTokenizer::pre_tokenize_str, 1))?;
 
     let class = module.define_class("Sequence", pre_tokenizer)?;
     class.define_singleton_method("new", function!(RbSequence::new, 1))?;
 
+    let class = module.define_class("Custom", pre_tokenizer)?;
+    class.define_singleton_method("new", function!(RbCustomPreTokenizer::new, 1))?;
+
     let class = module.define_class("BertPreTokenizer", pre_tokenizer)?;
     class.define_singleton_method("new", function!(RbBertPreTokenizer::new, 0))?;
 
@@ -476,6 +581,9 @@ pub fn init_pre_tokenizers(ruby: &Ruby, module: &RModule) -> RbResult<()> {
 
     let class = module.define_class("Split", pre_tokenizer)?;
     class.define_singleton_method("_new", function!(RbSplit::new, 3))?;
+    class.define_method("pattern", method!(RbPreTokenizer::split_pattern, 0))?;
+    class.define_method("behavior", method!(RbPreTokenizer::split_behavior, 0))?;
+    class.define_method("invert", method!(RbPreTokenizer::split_invert, 0))?;
 
     let class = module.define_class("UnicodeScripts", pre_tokenizer)?;
     class.define_singleton_method("new", function!(RbUnicodeScripts::new, 0))?;
@@ -1,18 +1,21 @@
 use std::sync::Arc;
 
 use magnus::{
-    data_type_builder, function, value::Lazy, Class, DataType, DataTypeFunctions, Module, Object, RClass, RModule,
-    Ruby, TryConvert, TypedData, Value,
+    data_type_builder, exception, function, method, value::Lazy, Class, DataType, DataTypeFunctions, Error, Module, Object,
+    RArray, RClass, RHash, RModule, Ruby, Symbol, TryConvert, TypedData, Value,
 };
 use serde::{Deserialize, Serialize};
 use tk::processors::bert::BertProcessing;
 use tk::processors::byte_level::ByteLevel;
 use tk::processors::roberta::RobertaProcessing;
+use tk::processors::sequence::Sequence;
 use tk::processors::template::{SpecialToken, Template};
 use tk::processors::PostProcessorWrapper;
 use tk::{Encoding, PostProcessor};
 
-use super::{PROCESSORS, RbResult};
+use super::encoding::RbEncoding;
+use super::utils::{deep_copy, dump, load, serialized_field};
+use super::{PROCESSORS, RbError, RbResult};
 
 #[derive(DataTypeFunctions, Clone, Deserialize, Serialize)]
 pub struct RbPostProcessor {
@@ -24,6 +27,43 @@ impl RbPostProcessor {
     pub fn new(processor: Arc<PostProcessorWrapper>) -> Self {
         RbPostProcessor { processor }
     }
+
+    pub fn copy(&self) -> RbResult<Self> {
+        deep_copy(self)
+    }
+
+    pub fn dump(&self, _level: i64) -> RbResult<String> {
+        dump(self)
+    }
+
+    pub fn load(json: String) -> RbResult<Self> {
+        load(&json)
+    }
+
+    pub fn process(
+        &self,
+        encoding: &RbEncoding,
+        pair: Option<&RbEncoding>,
+        add_special_tokens: bool,
+    ) -> RbResult<RbEncoding> {
+        self.processor
+            .process(
+                encoding.encoding.clone(),
+                pair.map(|p| p.encoding.clone()),
+                add_special_tokens,
+            )
+            .map(|v| v.into())
+            .map_err(RbError::encoding)
+    }
+
+    pub fn get_added_tokens(&self, is_pair: bool) -> usize {
+        self.processor.added_tokens(is_pair)
+    }
+
+    pub fn sequence_processors(&self) -> RbResult<Vec<RbPostProcessor>> {
+        let processors: Vec<PostProcessorWrapper> = serialized_field(self, "processors")?;
+        Ok(processors.into_iter().map(|p| RbPostProcessor::new(Arc::new(p))).collect())
+    }
 }
 
 impl PostProcessor for RbPostProcessor {
@@ -56,12 +96,33 @@ impl TryConvert for RbSpecialToken {
             Ok(Self(v.into()))
         } else if let Ok(v) = <(u32, String)>::try_convert(ob) {
             Ok(Self(v.into()))
+        } else if let Ok(h) = RHash::try_convert(ob) {
+            let id: String = special_token_entry(h, "id")?;
+            let ids: Vec<u32> = special_token_entry(h, "ids")?;
+            let tokens: Vec<String> = special_token_entry(h, "tokens")?;
+            SpecialToken::new(id, ids, tokens)
+                .map(Self)
+                .map_err(|e| Error::new(exception::arg_error(), e.to_string()))
         } else {
-            todo!()
+            Err(Error::new(
+                exception::type_error(),
+                "special token must be a [String, Integer] pair or a Hash with id, ids, and tokens",
+            ))
         }
     }
 }
 
+// allow symbol or string keys
+fn special_token_entry<T: TryConvert>(hash: RHash, key: &str) -> RbResult<T> {
+    match hash.get(Symbol::new(key)).or_else(|| hash.get(key)) {
+        Some(value) => T::try_convert(value),
+        None => Err(Error::new(
+            exception::arg_error(),
+            format!("special token is missing {}", key),
+        )),
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RbTemplate(Template);
 
@@ -75,14 +136,17 @@ impl TryConvert for RbTemplate {
     fn try_convert(ob: Value) -> RbResult<Self> {
         if let Ok(s) = String::try_convert(ob) {
             Ok(Self(
-                s.try_into().unwrap(), //.map_err(RbError::from)?,
+                s.try_into().map_err(|e: String| Error::new(exception::arg_error(), e))?,
             ))
         } else if let Ok(s) = <Vec<String>>::try_convert(ob) {
             Ok(Self(
-                s.try_into().unwrap(), //.map_err(RbError::from)?,
+                s.try_into().map_err(|e: String| Error::new(exception::arg_error(), e))?,
             ))
         } else {
-            todo!()
+            Err(Error::new(
+                exception::type_error(),
+                "template must be a String or an Array of Strings",
+            ))
         }
     }
 }
@@ -125,13 +189,26 @@ impl RbRobertaProcessing {
     }
 }
 
+pub struct RbSequence {}
+
+impl RbSequence {
+    pub fn new(processors: RArray) -> RbResult<RbPostProcessor> {
+        let mut sequence = Vec::with_capacity(processors.len());
+        for p in processors.each() {
+            let processor: &RbPostProcessor = TryConvert::try_convert(p?)?;
+            sequence.push((*processor.processor).clone());
+        }
+        Ok(RbPostProcessor::new(Arc::new(Sequence::new(sequence).into())))
+    }
+}
+
 pub struct RbTemplateProcessing {}
 
 impl RbTemplateProcessing {
     pub fn new(
         single: Option<RbTemplate>,
         pair: Option<RbTemplate>,
-        special_tokens: Option<Vec<(String, u32)>>,
+        special_tokens: Option<Vec<RbSpecialToken>>,
     ) -> RbResult<RbPostProcessor> {
         let mut builder = tk::processors::template::TemplateProcessing::builder();
 
@@ -144,7 +221,9 @@ impl RbTemplateProcessing {
         if let Some(sp) = special_tokens {
             builder.special_tokens(sp);
         }
-        let processor = builder.build().unwrap(); //.map_err(RbError::from)?;
+        let processor = builder
+            .build()
+            .map_err(|e| Error::new(exception::arg_error(), e.to_string()))?;
 
         Ok(RbPostProcessor::new(Arc::new(processor.into())))
     }
@@ -181,6 +260,11 @@ unsafe impl TypedData for RbPostProcessor {
             class.undef_default_alloc_func();
             class
         });
+        static SEQUENCE: Lazy<RClass> = Lazy::new(|ruby| {
+            let class: RClass = ruby.get_inner(&PROCESSORS).const_get("Sequence").unwrap();
+            class.undef_default_alloc_func();
+            class
+        });
         static TEMPLATE_PROCESSING: Lazy<RClass> = Lazy::new(|ruby| {
             let class: RClass = ruby.get_inner(&PROCESSORS).const_get("TemplateProcessing").unwrap();
             class.undef_default_alloc_func();
@@ -190,14 +274,19 @@ unsafe impl TypedData for RbPostProcessor {
             PostProcessorWrapper::Bert(_) => ruby.get_inner(&BERT_PROCESSING),
             PostProcessorWrapper::ByteLevel(_) => ruby.get_inner(&BYTE_LEVEL),
             PostProcessorWrapper::Roberta(_) => ruby.get_inner(&ROBERTA_PROCESSING),
+            PostProcessorWrapper::Sequence(_) => ruby.get_inner(&SEQUENCE),
             PostProcessorWrapper::Template(_) => ruby.get_inner(&TEMPLATE_PROCESSING),
-            _ => todo!(),
         }
     }
 }
 
 pub fn init_processors(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let post_processor = module.define_class("PostProcessor", ruby.class_object())?;
+    post_processor.define_method("_copy", method!(RbPostProcessor::copy, 0))?;
+    post_processor.define_method("_dump", method!(RbPostProcessor::dump, 1))?;
+    post_processor.define_singleton_method("_load", function!(RbPostProcessor::load, 1))?;
+    post_processor.define_method("_process", method!(RbPostProcessor::process, 3))?;
+    post_processor.define_method("added_tokens", method!(RbPostProcessor::get_added_tokens, 1))?;
 
     let class = module.define_class("BertProcessing", post_processor)?;
     class.define_singleton_method("new", function!(RbBertProcessing::new, 2))?;
@@ -208,6 +297,10 @@ pub fn init_processors(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let class = module.define_class("RobertaProcessing", post_processor)?;
     class.define_singleton_method("_new", function!(RbRobertaProcessing::new, 4))?;
 
+    let class = module.define_class("Sequence", post_processor)?;
+    class.define_singleton_method("new", function!(RbSequence::new, 1))?;
+    class.define_method("processors", method!(RbPostProcessor::sequence_processors, 0))?;
+
     let class = module.define_class("TemplateProcessing", post_processor)?;
     class.define_singleton_method("_new", function!(RbTemplateProcessing::new, 3))?;
 
@@ -1,26 +1,71 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::panic;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+use std::thread;
 
 use magnus::prelude::*;
-use magnus::{exception, Error, RArray, RHash, Symbol, TryConvert, Value};
+use magnus::{
+    exception, typed_data::Obj, Enumerator, Error, RArray, RHash, RString, Symbol, TryConvert, Value,
+};
 use tk::tokenizer::{
-    Model, PaddingDirection, PaddingParams, PaddingStrategy,
+    Model, PaddingDirection, PaddingParams, PaddingStrategy, Trainer,
     TruncationDirection, TruncationParams, TruncationStrategy, TokenizerImpl
 };
-use tk::AddedToken;
+use tk::parallelism::MaybeParallelIterator;
+use tk::utils::padding::pad_encodings;
 
-use crate::tk::PostProcessor;
+use crate::tk::{PostProcessor, PreTokenizer};
 
 use super::decoders::RbDecoder;
-use super::encoding::RbEncoding;
+use super::encoding::{RbBatchEncoding, RbEncoding};
 use super::models::RbModel;
 use super::normalizers::RbNormalizer;
 use super::pre_tokenizers::RbPreTokenizer;
 use super::processors::RbPostProcessor;
 use super::trainers::RbTrainer;
+use super::utils::{
+    check_interrupts, chunk_encoding, compress, decompress, in_callback, maybe_nogvl, nogvl, nogvl_cancelable,
+    with_callback, AddedTokenAction, AddedTokenSplitter, Chunk, ChunkBoundary, Utf16OffsetConverter,
+};
 use super::{RbError, RbResult};
 
+// inputs smaller than this are encoded while holding the GVL
+// since releasing and reacquiring it can cost more than the work itself
+const NOGVL_MIN_INPUT_LEN: usize = 16 * 1024;
+
+// number of batch items processed each time the GVL is released
+// interrupts are checked between chunks, so a single large input can't be interrupted
+const NOGVL_CHUNK_SIZE: usize = 256;
+
+// number of batches buffered between Ruby and the training thread
+const TRAIN_CHANNEL_BOUND: usize = 16;
+
+// number of special token policies with a splitter kept per tokenizer
+const MAX_CACHED_SPLITTERS: usize = 16;
+
+fn input_sequence_len(sequence: &tk::InputSequence) -> usize {
+    match sequence {
+        tk::InputSequence::Raw(s) => s.len(),
+        tk::InputSequence::PreTokenized(seq) => seq.iter().map(|s| s.len()).sum(),
+        tk::InputSequence::PreTokenizedOwned(seq) => seq.iter().map(|s| s.len()).sum(),
+        tk::InputSequence::PreTokenizedCow(seq) => seq.iter().map(|s| s.len()).sum(),
+    }
+}
+
+fn input_sequence_strs<'a>(sequence: &'a tk::InputSequence) -> Vec<&'a str> {
+    match sequence {
+        tk::InputSequence::Raw(s) => vec![&s[..]],
+        tk::InputSequence::PreTokenized(seq) => seq.to_vec(),
+        tk::InputSequence::PreTokenizedOwned(seq) => seq.iter().map(|s| s.as_str()).collect(),
+        tk::InputSequence::PreTokenizedCow(seq) => seq.iter().map(|s| &s[..]).collect(),
+    }
+}
+
+#[derive(Clone)]
+#[magnus::wrap(class = "Tokenizers::AddedToken")]
 pub struct RbAddedToken {
     pub content: String,
     pub is_special_token: bool,
@@ -60,6 +105,89 @@ impl RbAddedToken {
 
         token
     }
+
+    // accepts a string or an AddedToken
+    // is_special_token forces AddedTokens to be special, as with add_special_tokens
+    pub fn token_from_value(value: Value, is_special_token: bool) -> RbResult<tk::AddedToken> {
+        if let Ok(content) = String::try_convert(value) {
+            return Ok(RbAddedToken::from(content, Some(is_special_token)).get_token());
+        }
+        if let Ok(token) = <&RbAddedToken>::try_convert(value) {
+            let mut token = token.clone();
+            token.is_special_token |= is_special_token;
+            return Ok(token.get_token());
+        }
+        Err(Error::new(
+            exception::type_error(),
+            "token must be a String or Tokenizers::AddedToken",
+        ))
+    }
+
+    pub fn tokens_from_array(tokens: RArray, is_special_token: bool) -> RbResult<Vec<tk::AddedToken>> {
+        tokens
+            .each()
+            .map(|token| RbAddedToken::token_from_value(token?, is_special_token))
+            .collect()
+    }
+
+    pub fn new(content: String, kwargs: RHash) -> RbResult<Self> {
+        let mut token = RbAddedToken::from(content, None);
+
+        let value: Value = kwargs.delete(Symbol::new("special"))?;
+        if !value.is_nil() {
+            token.is_special_token = TryConvert::try_convert(value)?;
+        }
+
+        let value: Value = kwargs.delete(Symbol::new("single_word"))?;
+        if !value.is_nil() {
+            token.single_word = Some(TryConvert::try_convert(value)?);
+        }
+
+        let value: Value = kwargs.delete(Symbol::new("lstrip"))?;
+        if !value.is_nil() {
+            token.lstrip = Some(TryConvert::try_convert(value)?);
+        }
+
+        let value: Value = kwargs.delete(Symbol::new("rstrip"))?;
+        if !value.is_nil() {
+            token.rstrip = Some(TryConvert::try_convert(value)?);
+        }
+
+        let value: Value = kwargs.delete(Symbol::new("normalized"))?;
+        if !value.is_nil() {
+            token.normalized = Some(TryConvert::try_convert(value)?);
+        }
+
+        if !kwargs.is_empty() {
+            return Err(RbError::unknown_keywords(kwargs));
+        }
+
+        Ok(token)
+    }
+
+    pub fn get_content(&self) -> String {
+        self.content.clone()
+    }
+
+    pub fn get_special(&self) -> bool {
+        self.is_special_token
+    }
+
+    pub fn get_single_word(&self) -> bool {
+        self.get_token().single_word
+    }
+
+    pub fn get_lstrip(&self) -> bool {
+        self.get_token().lstrip
+    }
+
+    pub fn get_rstrip(&self) -> bool {
+        self.get_token().rstrip
+    }
+
+    pub fn get_normalized(&self) -> bool {
+        self.get_token().normalized
+    }
 }
 
 impl From<tk::AddedToken> for RbAddedToken {
@@ -70,7 +198,7 @@ impl From<tk::AddedToken> for RbAddedToken {
             lstrip: Some(token.lstrip),
             rstrip: Some(token.rstrip),
             normalized: Some(token.normalized),
-            is_special_token: !token.normalized,
+            is_special_token: token.special,
         }
     }
 }
@@ -111,7 +239,10 @@ impl<'s> TryConvert for PreTokenizedInputSequence<'s> {
         if let Ok(seq) = RbArrayStr::try_convert(ob) {
             return Ok(Self(seq.into()));
         }
-        todo!()
+        Err(Error::new(
+            exception::type_error(),
+            "PreTokenizedInputSequence must be an array of strings",
+        ))
     }
 }
 
@@ -134,8 +265,8 @@ impl<'s> TryConvert for TextEncodeInput<'s> {
         // TODO check if this branch is needed
         if let Ok(arr) = RArray::try_convert(ob) {
             if arr.len() == 2 {
-                let first = arr.entry::<TextInputSequence>(0).unwrap();
-                let second = arr.entry::<TextInputSequence>(1).unwrap();
+                let first = arr.entry::<TextInputSequence>(0)?;
+                let second = arr.entry::<TextInputSequence>(1)?;
                 return Ok(Self((first, second).into()));
             }
         }
@@ -167,8 +298,8 @@ impl<'s> TryConvert for PreTokenizedEncodeInput<'s> {
         // TODO check if this branch is needed
         if let Ok(arr) = RArray::try_convert(ob) {
             if arr.len() == 2 {
-                let first = arr.entry::<PreTokenizedInputSequence>(0).unwrap();
-                let second = arr.entry::<PreTokenizedInputSequence>(1).unwrap();
+                let first = arr.entry::<PreTokenizedInputSequence>(0)?;
+                let second = arr.entry::<PreTokenizedInputSequence>(1)?;
                 return Ok(Self((first, second).into()));
             }
         }
@@ -185,18 +316,451 @@ impl<'s> From<PreTokenizedEncodeInput<'s>> for tk::tokenizer::EncodeInput<'s> {
     }
 }
 
+// a batch of strings from train_from_iterator
+// each element can be a string or an array of strings
+struct TrainBatch(Vec<String>);
+
+impl TryConvert for TrainBatch {
+    fn try_convert(ob: Value) -> RbResult<Self> {
+        let mut sequences = Vec::new();
+        for item in RArray::try_convert(ob)?.to_vec::<Value>()? {
+            if let Ok(seq) = RArray::try_convert(item) {
+                sequences.extend(seq.to_vec::<String>()?);
+            } else {
+                sequences.push(String::try_convert(item)?);
+            }
+        }
+        Ok(Self(sequences))
+    }
+}
+
+// reports the expected length for the progress bar
+struct SizedIterator<I> {
+    iter: I,
+    length: Option<usize>,
+}
+
+impl<I: Iterator> Iterator for SizedIterator<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.length)
+    }
+}
+
+// stops feeding sequences once aborted and fails before the model is trained,
+// so the tokenizer is unchanged when training is aborted
+struct AbortableTrainer<'a> {
+    trainer: &'a mut RbTrainer,
+    aborted: &'a AtomicBool,
+}
+
+impl Trainer for AbortableTrainer<'_> {
+    type Model = RbModel;
+
+    fn should_show_progress(&self) -> bool {
+        self.trainer.should_show_progress()
+    }
+
+    fn train(&self, model: &mut RbModel) -> tk::Result<Vec<tk::AddedToken>> {
+        self.trainer.train(model)
+    }
+
+    fn feed<I, S, F>(&mut self, iterator: I, process: F) -> tk::Result<()>
+    where
+        I: Iterator<Item = S> + Send,
+        S: AsRef<str> + Send,
+        F: Fn(&str) -> tk::Result<Vec<String>> + Sync,
+    {
+        let aborted = self.aborted;
+        self.trainer
+            .feed(iterator.take_while(|_| !aborted.load(Ordering::SeqCst)), process)?;
+        if aborted.load(Ordering::SeqCst) {
+            return Err("Training aborted".into());
+        }
+        Ok(())
+    }
+}
+
 type Tokenizer = TokenizerImpl<RbModel, RbNormalizer, RbPreTokenizer, RbPostProcessor, RbDecoder>;
 
-#[magnus::wrap(class = "Tokenizers::Tokenizer")]
+fn sorted_added_tokens(tokenizer: &Tokenizer) -> Vec<(u32, tk::AddedToken)> {
+    let mut tokens: Vec<_> = tokenizer.get_added_tokens_decoder().into_iter().collect();
+    tokens.sort_unstable_by_key(|(id, _)| *id);
+    tokens
+}
+
+// the added vocabulary can't remove tokens, so it's replaced by rebuilding the tokenizer
+// ids are assigned as tokens are added, so tokens are re-added in id order
+// and None is returned when any token would get a different id
+fn rebuild_added_tokens(tokenizer: &Tokenizer, tokens: &[(u32, tk::AddedToken)]) -> RbResult<Option<Tokenizer>> {
+    let mut rebuilt = Tokenizer::new(tokenizer.get_model().clone());
+    if let Some(normalizer) = tokenizer.get_normalizer() {
+        rebuilt.with_normalizer(normalizer.clone());
+    }
+    if let Some(pretok) = tokenizer.get_pre_tokenizer() {
+        rebuilt.with_pre_tokenizer(pretok.clone());
+    }
+    if let Some(processor) = tokenizer.get_post_processor() {
+        rebuilt.with_post_processor(processor.clone());
+    }
+    if let Some(decoder) = tokenizer.get_decoder() {
+        rebuilt.with_decoder(decoder.clone());
+    }
+    rebuilt
+        .with_truncation(tokenizer.get_truncation().cloned())
+        .map_err(RbError::from)?;
+    rebuilt.with_padding(tokenizer.get_padding().cloned());
+    rebuilt.set_encode_special_tokens(tokenizer.get_encode_special_tokens());
+    rebuilt.add_tokens(&tokens.iter().map(|(_, token)| token.clone()).collect::<Vec<_>>());
+    if tokens
+        .iter()
+        .any(|(id, token)| rebuilt.token_to_id(&token.content) != Some(*id))
+    {
+        return Ok(None);
+    }
+    Ok(Some(rebuilt))
+}
+
+#[derive(Clone, Copy)]
+enum OffsetType {
+    Char,
+    Byte,
+    Utf16,
+}
+
+impl TryConvert for OffsetType {
+    fn try_convert(ob: Value) -> RbResult<Self> {
+        let value = String::try_convert(ob)?;
+        match value.as_str() {
+            "char" => Ok(OffsetType::Char),
+            "byte" => Ok(OffsetType::Byte),
+            "utf16" => Ok(OffsetType::Utf16),
+            _ => Err(Error::new(exception::arg_error(), "The offset_type value must be 'char', 'byte', or 'utf16'")),
+        }
+    }
+}
+
+// added tokens are split first, then the rest is normalized, pre-tokenized, and tokenized
+fn tokenize_sequence(
+    tokenizer: &Tokenizer,
+    splitter: &AddedTokenSplitter,
+    sequence: &str,
+) -> tk::Result<tk::PreTokenizedString> {
+    let mut pretokenized = splitter.extract_and_normalize(tokenizer.get_normalizer(), sequence)?;
+    if let Some(pretok) = tokenizer.get_pre_tokenizer() {
+        pretok.pre_tokenize(&mut pretokenized)?;
+    }
+    let model = tokenizer.get_model();
+    pretokenized.tokenize(|normalized| model.tokenize(normalized.get()))?;
+    Ok(pretokenized)
+}
+
+// same as encode in tokenizers, but without post-processing
+fn encode_sequence(
+    tokenizer: &Tokenizer,
+    splitter: &AddedTokenSplitter,
+    sequence: &tk::InputSequence,
+    type_id: u32,
+    offset_type: tk::OffsetType,
+) -> tk::Result<tk::Encoding> {
+    let is_pretokenized = !matches!(sequence, tk::InputSequence::Raw(_));
+    input_sequence_strs(sequence)
+        .into_iter()
+        .enumerate()
+        .map(|(i, subseq)| {
+            let word_idx = if is_pretokenized { Some(i as u32) } else { None };
+            tokenize_sequence(tokenizer, splitter, subseq)?.into_encoding(word_idx, type_id, offset_type)
+        })
+        .collect()
+}
+
+fn encode_with_splitter(
+    tokenizer: &Tokenizer,
+    splitter: Option<&AddedTokenSplitter>,
+    input: tk::EncodeInput,
+    add_special_tokens: bool,
+    offset_type: tk::OffsetType,
+) -> tk::Result<tk::Encoding> {
+    let splitter = match splitter {
+        Some(splitter) => splitter,
+        None => {
+            let encoding = match offset_type {
+                tk::OffsetType::Char => tokenizer.encode_char_offsets(input, add_special_tokens),
+                tk::OffsetType::Byte => tokenizer.encode(input, add_special_tokens),
+            }?;
+            RbError::check_callback()?;
+            return Ok(encoding);
+        }
+    };
+    let (sequence, pair) = match &input {
+        tk::EncodeInput::Single(sequence) => (sequence, None),
+        tk::EncodeInput::Dual(sequence, pair) => (sequence, Some(pair)),
+    };
+    let encoding = encode_sequence(tokenizer, splitter, sequence, 0, offset_type)?;
+    let pair_encoding = pair
+        .map(|pair| encode_sequence(tokenizer, splitter, pair, 1, offset_type))
+        .transpose()?;
+    tokenizer.post_process(encoding, pair_encoding, add_special_tokens)
+}
+
+fn encode_with_offsets(
+    tokenizer: &Tokenizer,
+    splitter: Option<&AddedTokenSplitter>,
+    input: tk::EncodeInput,
+    add_special_tokens: bool,
+    is_pretokenized: bool,
+    offset_type: OffsetType,
+) -> tk::Result<tk::Encoding> {
+    match offset_type {
+        OffsetType::Char => encode_with_splitter(tokenizer, splitter, input, add_special_tokens, tk::OffsetType::Char),
+        OffsetType::Byte => encode_with_splitter(tokenizer, splitter, input, add_special_tokens, tk::OffsetType::Byte),
+        OffsetType::Utf16 => {
+            let sequences = match &input {
+                tk::EncodeInput::Single(sequence) => vec![input_sequence_strs(sequence)],
+                tk::EncodeInput::Dual(sequence, pair) => {
+                    vec![input_sequence_strs(sequence), input_sequence_strs(pair)]
+                }
+            }
+            .into_iter()
+            .map(|strs| strs.into_iter().map(|s| s.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+            let mut encoding =
+                encode_with_splitter(tokenizer, splitter, input, add_special_tokens, tk::OffsetType::Byte)?;
+            utf16_offsets(&mut encoding, &sequences, is_pretokenized);
+            Ok(encoding)
+        }
+    }
+}
+
+// custom components call into Ruby, so they run while holding the GVL and not in parallel
+fn has_custom_components(tokenizer: &Tokenizer) -> bool {
+    tokenizer.get_normalizer().is_some_and(|n| n.is_custom())
+        || tokenizer.get_pre_tokenizer().is_some_and(|p| p.is_custom())
+}
+
+// Ruby code called while holding the lock can't wait for it
+fn tokenizer_in_use() -> Error {
+    RbError::from("Cannot use a tokenizer from a callback while it's in use".into())
+}
+
+// trainers process sequences on other threads, where Ruby can't be called
+fn check_trainable(tokenizer: &Tokenizer) -> RbResult<()> {
+    if has_custom_components(tokenizer) {
+        return Err(RbError::training("Training is not supported with custom components".into()));
+    }
+    Ok(())
+}
+
+// counts tokens without building an encoding, so truncation and padding don't apply
+fn count_tokens(
+    tokenizer: &Tokenizer,
+    splitter: &AddedTokenSplitter,
+    text: &str,
+    add_special_tokens: bool,
+) -> tk::Result<usize> {
+    let pretokenized = tokenize_sequence(tokenizer, splitter, text)?;
+    let count: usize = pretokenized
+        .get_splits(tk::OffsetReferential::Original, tk::OffsetType::Byte)
+        .into_iter()
+        .map(|(_, _, tokens)| tokens.as_ref().map_or(0, |t| t.len()))
+        .sum();
+    let special_count = match tokenizer.get_post_processor() {
+        Some(processor) if add_special_tokens => processor.added_tokens(false),
+        _ => 0,
+    };
+    Ok(count + special_count)
+}
+
+// converts byte offsets in place
+// pre-tokenized offsets are relative to each word, so words are looked up by word id
+fn utf16_offsets(encoding: &mut tk::Encoding, sequences: &[Vec<String>], is_pretokenized: bool) {
+    let sequence_ids = encoding.get_sequence_ids();
+    let word_ids = encoding.get_word_ids().to_vec();
+    let mut converters: HashMap<(usize, usize), Utf16OffsetConverter> = HashMap::new();
+    for (i, offsets) in encoding.get_offsets_mut().iter_mut().enumerate() {
+        let (sequence_id, word_id) = match (sequence_ids[i], word_ids[i]) {
+            (Some(sequence_id), Some(word_id)) => (sequence_id, word_id),
+            _ => continue,
+        };
+        let index = if is_pretokenized { word_id as usize } else { 0 };
+        let text = match sequences.get(sequence_id).and_then(|s| s.get(index)) {
+            Some(text) => text,
+            None => continue,
+        };
+        let converter = converters
+            .entry((sequence_id, index))
+            .or_insert_with(|| Utf16OffsetConverter::new(text));
+        if let Some(converted) = converter.convert(*offsets) {
+            *offsets = converted;
+        }
+    }
+    for overflowing in encoding.get_overflowing_mut() {
+        utf16_offsets(overflowing, sequences, is_pretokenized);
+    }
+}
+
+// special tokens given to allowed_special and disallowed_special
+// ordered so policies can be used as cache keys
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum SpecialTokens {
+    All,
+    Only(BTreeSet<String>),
+}
+
+impl SpecialTokens {
+    fn contains(&self, token: &str) -> bool {
+        match self {
+            SpecialTokens::All => true,
+            SpecialTokens::Only(tokens) => tokens.contains(token),
+        }
+    }
+}
+
+impl TryConvert for SpecialTokens {
+    fn try_convert(ob: Value) -> RbResult<Self> {
+        if let Ok(sym) = Symbol::try_convert(ob) {
+            if sym.name()? == "all" {
+                return Ok(SpecialTokens::All);
+            }
+        } else if let Ok(arr) = RArray::try_convert(ob) {
+            let tokens = RbAddedToken::tokens_from_array(arr, false)?;
+            return Ok(SpecialTokens::Only(
+                tokens.into_iter().map(|token| token.content).collect(),
+            ));
+        }
+        Err(Error::new(
+            exception::arg_error(),
+            "special tokens must be :all or an array",
+        ))
+    }
+}
+
+// decides how special tokens in the input are encoded, like tiktoken
+// allowed tokens are encoded as special tokens, disallowed tokens raise an error,
+// and everything else is encoded as plain text
+// no tokens are allowed when only disallowed tokens are given
+// the default policy matches added tokens the same way as the tokenizer
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+struct SpecialTokensPolicy {
+    allowed: Option<SpecialTokens>,
+    disallowed: Option<SpecialTokens>,
+}
+
+impl SpecialTokensPolicy {
+    fn is_default(&self) -> bool {
+        self.allowed.is_none() && self.disallowed.is_none()
+    }
+
+    fn splitter(&self, tokenizer: &Tokenizer) -> tk::Result<AddedTokenSplitter> {
+        let encode_special_tokens = tokenizer.get_encode_special_tokens();
+        let tokens = sorted_added_tokens(tokenizer)
+            .into_iter()
+            .filter_map(|(id, token)| {
+                let action = if self.is_default() {
+                    if encode_special_tokens && token.special {
+                        return None;
+                    }
+                    AddedTokenAction::Match
+                } else if !token.special || self.allowed.as_ref().is_some_and(|a| a.contains(&token.content)) {
+                    AddedTokenAction::Match
+                } else if self.disallowed.as_ref().is_some_and(|d| d.contains(&token.content)) {
+                    AddedTokenAction::Disallow
+                } else {
+                    return None;
+                };
+                Some((id, token, action))
+            })
+            .collect();
+        AddedTokenSplitter::new(tokens, tokenizer.get_normalizer())
+    }
+}
+
+// frozen tokenizers can be shared across Ractors
+// state is behind a lock since it can be used from multiple threads at once
+#[magnus::wrap(class = "Tokenizers::Tokenizer", frozen_shareable)]
 pub struct RbTokenizer {
-    tokenizer: RefCell<Tokenizer>,
+    tokenizer: RwLock<Tokenizer>,
+    // built from the added vocabulary and normalizer, and cleared whenever the tokenizer is changed
+    splitters: Mutex<HashMap<SpecialTokensPolicy, Arc<AddedTokenSplitter>>>,
 }
 
 impl RbTokenizer {
     pub fn new(tokenizer: Tokenizer) -> Self {
         Self {
-            tokenizer: RefCell::new(tokenizer),
+            tokenizer: RwLock::new(tokenizer),
+            splitters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // only block on the lock without the GVL, otherwise a thread holding
+    // the lock and waiting to reacquire the GVL would deadlock
+    // callbacks raise instead of blocking, since the lock may be held by their own thread
+    // every call starts here, so exceptions left by earlier calls are cleared
+    // a panic while holding the lock is already raised as an exception,
+    // so poisoned locks are recovered instead of panicking on every later call
+    fn tokenizer(&self) -> RbResult<RwLockReadGuard<'_, Tokenizer>> {
+        RbError::reset_callback();
+        match self.tokenizer.try_read() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::WouldBlock) if in_callback() => Err(tokenizer_in_use()),
+            Err(TryLockError::WouldBlock) => Ok(nogvl(|| self.tokenizer.read().unwrap_or_else(|e| e.into_inner()))),
+            Err(TryLockError::Poisoned(e)) => Ok(e.into_inner()),
+        }
+    }
+
+    fn tokenizer_mut(&self) -> RbResult<RwLockWriteGuard<'_, Tokenizer>> {
+        RbError::reset_callback();
+        let guard = match self.tokenizer.try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) if in_callback() => return Err(tokenizer_in_use()),
+            Err(TryLockError::WouldBlock) => nogvl(|| self.tokenizer.write().unwrap_or_else(|e| e.into_inner())),
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        };
+        // every change starts here
+        self.clear_splitters();
+        Ok(guard)
+    }
+
+    // changes made through component references aren't detected, like with the upstream added vocabulary
+    fn clear_splitters(&self) {
+        self.splitters.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+
+    // must be called with the tokenizer locked, so it can't change while the splitter is used
+    // the cache isn't locked while building, since custom normalizers can call back into this tokenizer
+    fn cached_splitter(
+        &self,
+        tokenizer: &Tokenizer,
+        policy: &SpecialTokensPolicy,
+    ) -> RbResult<Arc<AddedTokenSplitter>> {
+        if let Some(splitter) = self.splitters.lock().unwrap_or_else(PoisonError::into_inner).get(policy) {
+            return Ok(splitter.clone());
+        }
+        let splitter = Arc::new(policy.splitter(tokenizer).map_err(RbError::encoding)?);
+        let mut splitters = self.splitters.lock().unwrap_or_else(PoisonError::into_inner);
+        // policies come from user input, so only keep the most recent ones
+        if splitters.len() >= MAX_CACHED_SPLITTERS {
+            splitters.clear();
+        }
+        splitters.insert(policy.clone(), splitter.clone());
+        Ok(splitter)
+    }
+
+    // returns a splitter when the input needs to be matched differently than the tokenizer does
+    fn policy_splitter(
+        &self,
+        tokenizer: &Tokenizer,
+        policy: &SpecialTokensPolicy,
+    ) -> RbResult<Option<Arc<AddedTokenSplitter>>> {
+        if policy.is_default() {
+            return Ok(None);
         }
+        self.cached_splitter(tokenizer, policy).map(Some)
     }
 
     pub fn from_model(model: &RbModel) -> Self {
@@ -204,44 +768,141 @@ impl RbTokenizer {
     }
 
     pub fn from_file(path: PathBuf) -> RbResult<Self> {
-        Tokenizer::from_file(path)
-            .map(|v| RbTokenizer {
-                tokenizer: RefCell::new(v),
-            })
-            .map_err(RbError::from)
+        nogvl(|| -> tk::Result<Tokenizer> {
+            let bytes = decompress(fs::read(&path)?)?;
+            Tokenizer::from_bytes(bytes)
+        })
+        .map(RbTokenizer::new)
+        .map_err(|e| RbError::with_path(e, &path))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(json: String) -> RbResult<Self> {
+        nogvl(|| json.parse::<Tokenizer>())
+            .map(RbTokenizer::new)
+            .map_err(RbError::serialization)
+    }
+
+    pub fn from_buffer(buffer: RString) -> RbResult<Self> {
+        let bytes = unsafe { buffer.as_slice() }.to_vec();
+        nogvl(|| decompress(bytes).and_then(Tokenizer::from_bytes))
+            .map(RbTokenizer::new)
+            .map_err(RbError::serialization)
+    }
+
+    // cloning would share the components, so round trip through JSON instead
+    pub fn copy(&self) -> RbResult<Self> {
+        let (json, encode_special_tokens) = {
+            let tokenizer = self.tokenizer()?;
+            let json = tokenizer.to_string(false).map_err(RbError::serialization)?;
+            (json, tokenizer.get_encode_special_tokens())
+        };
+        let mut tokenizer = nogvl(|| json.parse::<Tokenizer>()).map_err(RbError::serialization)?;
+        tokenizer.set_encode_special_tokens(encode_special_tokens);
+        Ok(RbTokenizer::new(tokenizer))
     }
 
     pub fn to_str(&self, pretty: bool) -> RbResult<String> {
-        self.tokenizer.borrow().to_string(pretty).map_err(RbError::from)
+        self.tokenizer()?.to_string(pretty).map_err(RbError::serialization)
     }
 
-    pub fn add_special_tokens(&self, tokens: Vec<String>) -> usize {
-        let tokens: Vec<AddedToken> = tokens.iter().map(|t| AddedToken::from(t, true)).collect();
-        self.tokenizer.borrow_mut().add_special_tokens(&tokens)
+    pub fn add_special_tokens(rb_self: Obj<Self>, tokens: RArray) -> RbResult<usize> {
+        rb_self.check_frozen()?;
+        let tokens = RbAddedToken::tokens_from_array(tokens, true)?;
+        Ok(rb_self.tokenizer_mut()?.add_special_tokens(&tokens))
     }
 
-    pub fn train(&self, files: Vec<String>, trainer: Option<&RbTrainer>) -> RbResult<()> {
-        let mut trainer = trainer.map_or_else(
-            || self.tokenizer.borrow().get_model().get_trainer(),
-            |t| t.clone(),
-        );
-        self.tokenizer
-            .borrow_mut()
-            .train_from_files(&mut trainer, files)
-            .map(|_| {})
-            .map_err(RbError::from)
+    pub fn train(rb_self: Obj<Self>, files: Vec<String>, trainer: Option<&RbTrainer>) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        // checked while holding the lock, so components can't change before training
+        let mut tokenizer = rb_self.tokenizer_mut()?;
+        check_trainable(&tokenizer)?;
+        let mut trainer = trainer.map_or_else(|| tokenizer.get_model().get_trainer(), |t| t.clone());
+        // interrupts abort reading the files, but not building the model
+        let cancelled = AtomicBool::new(false);
+        let trained = nogvl_cancelable(&cancelled, || {
+            let mut trainer = AbortableTrainer {
+                trainer: &mut trainer,
+                aborted: &cancelled,
+            };
+            tokenizer.train_from_files(&mut trainer, files).map(|_| {})
+        });
+        check_interrupts()?;
+        trained.map_err(RbError::training)
     }
 
-    pub fn save(&self, path: String, pretty: bool) -> RbResult<()> {
-        self.tokenizer
-            .borrow()
-            .save(&path, pretty)
-            .map_err(RbError::from)
+    pub fn train_from_iterator(
+        rb_self: Obj<Self>,
+        batches: Enumerator,
+        trainer: Option<&RbTrainer>,
+        length: Option<usize>,
+    ) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        let mut guard = rb_self.tokenizer_mut()?;
+        check_trainable(&guard)?;
+        let mut trainer = trainer.map_or_else(|| guard.get_model().get_trainer(), |t| t.clone());
+        let tokenizer: &mut Tokenizer = &mut guard;
+
+        // Ruby objects can only be used from this thread, so batches are pulled here
+        // and sent to a separate thread that feeds them to the trainer
+        // training is aborted if pulling a batch fails or the thread is interrupted,
+        // so a partial corpus isn't trained
+        let (sender, receiver) = mpsc::sync_channel::<Vec<String>>(TRAIN_CHANNEL_BOUND);
+        let aborted = AtomicBool::new(false);
+        thread::scope(|scope| {
+            let aborted = &aborted;
+            let handle = scope.spawn(move || {
+                let sequences = SizedIterator {
+                    iter: receiver.into_iter().flatten(),
+                    length,
+                };
+                let mut trainer = AbortableTrainer {
+                    trainer: &mut trainer,
+                    aborted,
+                };
+                tokenizer.train(&mut trainer, sequences).map(|_| {})
+            });
+
+            let produced = with_callback(|| -> RbResult<()> {
+                for batch in batches {
+                    let batch = TrainBatch::try_convert(batch?)?;
+                    if nogvl_cancelable(aborted, || sender.send(batch.0)).is_err() {
+                        // training thread stopped early
+                        break;
+                    }
+                    check_interrupts()?;
+                }
+                Ok(())
+            });
+            if produced.is_err() {
+                aborted.store(true, Ordering::SeqCst);
+            }
+            drop(sender);
+
+            let trained = match nogvl_cancelable(aborted, || handle.join()) {
+                Ok(v) => v,
+                Err(e) => panic::resume_unwind(e),
+            };
+            produced?;
+            check_interrupts()?;
+            trained.map_err(RbError::training)
+        })
+    }
+
+    pub fn save(&self, path: PathBuf, pretty: bool) -> RbResult<()> {
+        let serialized = self.to_str(pretty)?;
+        nogvl(|| -> tk::Result<()> {
+            let bytes = compress(serialized.into_bytes(), &path)?;
+            fs::write(&path, bytes)?;
+            Ok(())
+        })
+        .map_err(|e| RbError::with_path(e, &path))
     }
 
-    pub fn add_tokens(&self, tokens: Vec<String>) -> usize {
-        let tokens: Vec<AddedToken> = tokens.iter().map(|t| AddedToken::from(t, true)).collect();
-        self.tokenizer.borrow_mut().add_tokens(&tokens)
+    pub fn add_tokens(rb_self: Obj<Self>, tokens: RArray) -> RbResult<usize> {
+        rb_self.check_frozen()?;
+        let tokens = RbAddedToken::tokens_from_array(tokens, false)?;
+        Ok(rb_self.tokenizer_mut()?.add_tokens(&tokens))
     }
 
     pub fn encode(
@@ -250,12 +911,16 @@ impl RbTokenizer {
         pair: Option<Value>,
         is_pretokenized: bool,
         add_special_tokens: bool,
+        allowed_special: Option<SpecialTokens>,
+        disallowed_special: Option<SpecialTokens>,
+        offset_type: OffsetType,
     ) -> RbResult<RbEncoding> {
         let sequence: tk::InputSequence = if is_pretokenized {
             PreTokenizedInputSequence::try_convert(sequence)?.into()
         } else {
             TextInputSequence::try_convert(sequence)?.into()
         };
+        let mut input_len = input_sequence_len(&sequence);
         let input = match pair {
             Some(pair) => {
                 let pair: tk::InputSequence = if is_pretokenized {
@@ -263,16 +928,26 @@ impl RbTokenizer {
                 } else {
                     TextInputSequence::try_convert(pair)?.into()
                 };
+                input_len += input_sequence_len(&pair);
                 tk::EncodeInput::Dual(sequence, pair)
             }
             None => tk::EncodeInput::Single(sequence),
         };
 
-        self.tokenizer
-            .borrow()
-            .encode_char_offsets(input, add_special_tokens)
+        let guard = self.tokenizer()?;
+        let policy = SpecialTokensPolicy {
+            allowed: allowed_special,
+            disallowed: disallowed_special,
+        };
+        let splitter = self.policy_splitter(&guard, &policy)?;
+        let tokenizer: &Tokenizer = &guard;
+        let release_gvl = input_len >= NOGVL_MIN_INPUT_LEN && !has_custom_components(tokenizer);
+        let encoding = maybe_nogvl(release_gvl, || {
+            encode_with_offsets(tokenizer, splitter.as_deref(), input, add_special_tokens, is_pretokenized, offset_type)
+        });
+        encoding
             .map(|v| RbEncoding { encoding: v })
-            .map_err(RbError::from)
+            .map_err(RbError::encoding)
     }
 
     pub fn encode_batch(
@@ -280,7 +955,11 @@ impl RbTokenizer {
         input: RArray,
         is_pretokenized: bool,
         add_special_tokens: bool,
-    ) -> RbResult<RArray> {
+        allowed_special: Option<SpecialTokens>,
+        disallowed_special: Option<SpecialTokens>,
+        offset_type: OffsetType,
+        return_overflowing_tokens: bool,
+    ) -> RbResult<RbBatchEncoding> {
         let input: Vec<tk::EncodeInput> = input
             .each()
             .map(|o| {
@@ -292,65 +971,261 @@ impl RbTokenizer {
                 Ok(input)
             })
             .collect::<RbResult<Vec<tk::EncodeInput>>>()?;
-        self.tokenizer
-            .borrow()
-            .encode_batch_char_offsets(input, add_special_tokens)
-            .map(|encodings| {
-                encodings
-                    .into_iter()
-                    .map(Into::<RbEncoding>::into)
-                    .collect()
+
+        let guard = self.tokenizer()?;
+        let policy = SpecialTokensPolicy {
+            allowed: allowed_special,
+            disallowed: disallowed_special,
+        };
+        let splitter = self.policy_splitter(&guard, &policy)?;
+        let tokenizer: &Tokenizer = &guard;
+
+        let custom = has_custom_components(tokenizer);
+
+        // same as encode_batch, but in chunks
+        // so the GVL can be released and interrupts checked
+        let mut encodings = Vec::with_capacity(input.len());
+        let mut input = input.into_iter();
+        loop {
+            let chunk: Vec<tk::EncodeInput> = input.by_ref().take(NOGVL_CHUNK_SIZE).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            let chunk_encodings = maybe_nogvl(!custom, || {
+                chunk
+                    .into_maybe_par_iter_cond(!custom)
+                    .map(|input| {
+                        encode_with_offsets(
+                            tokenizer,
+                            splitter.as_deref(),
+                            input,
+                            add_special_tokens,
+                            is_pretokenized,
+                            offset_type,
+                        )
+                    })
+                    .collect::<tk::Result<Vec<tk::Encoding>>>()
             })
-            .map_err(RbError::from)
+            .map_err(RbError::encoding)?;
+            encodings.extend(chunk_encodings);
+            check_interrupts()?;
+        }
+
+        if let Some(params) = tokenizer.get_padding() {
+            // pad after all chunks to handle batch padding
+            pad_encodings(&mut encodings, params).map_err(RbError::encoding)?;
+        }
+
+        Ok(RbBatchEncoding::new(encodings, return_overflowing_tokens))
+    }
+
+    pub fn count_tokens(&self, text: String, add_special_tokens: bool) -> RbResult<usize> {
+        let tokenizer = self.tokenizer()?;
+        let splitter = self.cached_splitter(&tokenizer, &SpecialTokensPolicy::default())?;
+        let release_gvl = text.len() >= NOGVL_MIN_INPUT_LEN && !has_custom_components(&tokenizer);
+        maybe_nogvl(release_gvl, || count_tokens(&tokenizer, &splitter, &text, add_special_tokens))
+            .map_err(RbError::encoding)
+    }
+
+    pub fn count_tokens_batch(&self, texts: Vec<String>, add_special_tokens: bool) -> RbResult<Vec<usize>> {
+        let guard = self.tokenizer()?;
+        let tokenizer: &Tokenizer = &guard;
+        let splitter = self.cached_splitter(tokenizer, &SpecialTokensPolicy::default())?;
+
+        let custom = has_custom_components(tokenizer);
+        let mut counts = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(NOGVL_CHUNK_SIZE) {
+            let chunk_counts = maybe_nogvl(!custom, || {
+                chunk
+                    .into_maybe_par_iter_cond(!custom)
+                    .map(|text| count_tokens(tokenizer, &splitter, text, add_special_tokens))
+                    .collect::<tk::Result<Vec<usize>>>()
+            })
+            .map_err(RbError::encoding)?;
+            counts.extend(chunk_counts);
+            check_interrupts()?;
+        }
+        Ok(counts)
+    }
+
+    pub fn chunk(&self, text: String, max_tokens: usize, overlap: usize, respect: ChunkBoundary) -> RbResult<RArray> {
+        if max_tokens == 0 {
+            return Err(Error::new(exception::arg_error(), "max_tokens must be greater than 0"));
+        }
+        if overlap >= max_tokens {
+            return Err(Error::new(exception::arg_error(), "overlap must be less than max_tokens"));
+        }
+
+        let chunks = {
+            let tokenizer = self.tokenizer()?;
+            let splitter = self.cached_splitter(&tokenizer, &SpecialTokensPolicy::default())?;
+            // encoded without truncation and padding
+            let chunk = || -> tk::Result<Vec<Chunk>> {
+                let sequence = tk::InputSequence::from(text.as_str());
+                let mut encoding = encode_sequence(&tokenizer, &splitter, &sequence, 0, tk::OffsetType::Byte)?;
+                // post-processors can change offsets even without adding special tokens
+                if let Some(processor) = tokenizer.get_post_processor() {
+                    encoding = processor.process(encoding, None, false)?;
+                }
+                Ok(chunk_encoding(&text, &encoding, max_tokens, overlap, respect))
+            };
+            let release_gvl = text.len() >= NOGVL_MIN_INPUT_LEN && !has_custom_components(&tokenizer);
+            maybe_nogvl(release_gvl, chunk).map_err(RbError::encoding)?
+        };
+
+        chunks
+            .into_iter()
+            .map(|chunk| {
+                let hash = RHash::new();
+                hash.aset("text", &text[chunk.bytes])?;
+                hash.aset("offsets", (chunk.chars.start, chunk.chars.end))?;
+                hash.aset("token_count", chunk.token_count)?;
+                Ok(hash)
+            })
+            .collect()
+    }
+
+    pub fn encode_special_tokens(&self) -> RbResult<bool> {
+        Ok(self.tokenizer()?.get_encode_special_tokens())
+    }
+
+    pub fn set_encode_special_tokens(rb_self: Obj<Self>, value: bool) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        rb_self.tokenizer_mut()?.set_encode_special_tokens(value);
+        Ok(())
     }
 
     pub fn decode(&self, ids: Vec<u32>, skip_special_tokens: bool) -> RbResult<String> {
-        self.tokenizer
-            .borrow()
+        self.tokenizer()?
             .decode(&ids, skip_special_tokens)
             .map_err(RbError::from)
     }
 
     pub fn decode_batch(&self, sequences: Vec<Vec<u32>>, skip_special_tokens: bool) -> RbResult<Vec<String>> {
-        let slices = sequences.iter().map(|v| &v[..]).collect::<Vec<&[u32]>>();
-        self.tokenizer
-            .borrow()
-            .decode_batch(&slices, skip_special_tokens)
-            .map_err(RbError::from)
+        let tokenizer = self.tokenizer()?;
+        let mut decoded = Vec::with_capacity(sequences.len());
+        for chunk in sequences.chunks(NOGVL_CHUNK_SIZE) {
+            let slices = chunk.iter().map(|v| &v[..]).collect::<Vec<&[u32]>>();
+            decoded.extend(
+                nogvl(|| tokenizer.decode_batch(&slices, skip_special_tokens))
+                    .map_err(RbError::from)?,
+            );
+            check_interrupts()?;
+        }
+        Ok(decoded)
     }
 
-    pub fn set_decoder(&self, decoder: &RbDecoder) {
-        self.tokenizer.borrow_mut().with_decoder(decoder.clone());
+    // called before freezing, so components returned or assigned earlier can't change the tokenizer
+    // custom components have no state to change, so they're kept
+    pub fn detach_components(&self) -> RbResult<()> {
+        let mut tokenizer = self.tokenizer_mut()?;
+        let model = tokenizer.get_model().copy()?;
+        let normalizer = match tokenizer.get_normalizer() {
+            Some(n) if !n.is_custom() => Some(n.copy()?),
+            _ => None,
+        };
+        let pretok = match tokenizer.get_pre_tokenizer() {
+            Some(p) if !p.is_custom() => Some(p.copy()?),
+            _ => None,
+        };
+        let processor = tokenizer.get_post_processor().map(|p| p.copy()).transpose()?;
+        let decoder = tokenizer.get_decoder().map(|d| d.copy()).transpose()?;
+
+        tokenizer.with_model(model);
+        if let Some(normalizer) = normalizer {
+            tokenizer.with_normalizer(normalizer);
+        }
+        if let Some(pretok) = pretok {
+            tokenizer.with_pre_tokenizer(pretok);
+        }
+        if let Some(processor) = processor {
+            tokenizer.with_post_processor(processor);
+        }
+        if let Some(decoder) = decoder {
+            tokenizer.with_decoder(decoder);
+        }
+        Ok(())
     }
 
-    pub fn set_pre_tokenizer(&self, pretok: &RbPreTokenizer) {
-        self.tokenizer
-            .borrow_mut()
-            .with_pre_tokenizer(pretok.clone());
+    // components share their state with the tokenizer, so changes apply in place
+    // frozen tokenizers return copies, so they can't be changed through their components
+    pub fn get_model(rb_self: Obj<Self>) -> RbResult<RbModel> {
+        let model = rb_self.tokenizer()?.get_model().clone();
+        if rb_self.is_frozen() {
+            model.copy()
+        } else {
+            Ok(model)
+        }
     }
 
-    pub fn set_post_processor(&self, processor: &RbPostProcessor) {
-        self.tokenizer
-            .borrow_mut()
-            .with_post_processor(processor.clone());
+    // custom components have no state to change, so they're never copied
+    pub fn get_normalizer(rb_self: Obj<Self>) -> RbResult<Option<RbNormalizer>> {
+        let normalizer = rb_self.tokenizer()?.get_normalizer().cloned();
+        match normalizer {
+            Some(n) if rb_self.is_frozen() && !n.is_custom() => n.copy().map(Some),
+            n => Ok(n),
+        }
     }
 
-    pub fn set_normalizer(&self, normalizer: &RbNormalizer) {
-        self.tokenizer
-            .borrow_mut()
-            .with_normalizer(normalizer.clone());
+    pub fn get_pre_tokenizer(rb_self: Obj<Self>) -> RbResult<Option<RbPreTokenizer>> {
+        let pretok = rb_self.tokenizer()?.get_pre_tokenizer().cloned();
+        match pretok {
+            Some(p) if rb_self.is_frozen() && !p.is_custom() => p.copy().map(Some),
+            p => Ok(p),
+        }
     }
 
-    pub fn token_to_id(&self, token: String) -> Option<u32> {
-        self.tokenizer.borrow().token_to_id(&token)
+    pub fn get_post_processor(rb_self: Obj<Self>) -> RbResult<Option<RbPostProcessor>> {
+        let processor = rb_self.tokenizer()?.get_post_processor().cloned();
+        match processor {
+            Some(p) if rb_self.is_frozen() => p.copy().map(Some),
+            p => Ok(p),
+        }
     }
 
-    pub fn id_to_token(&self, id: u32) -> Option<String> {
-        self.tokenizer.borrow().id_to_token(id)
+    pub fn get_decoder(rb_self: Obj<Self>) -> RbResult<Option<RbDecoder>> {
+        let decoder = rb_self.tokenizer()?.get_decoder().cloned();
+        match decoder {
+            Some(d) if rb_self.is_frozen() => d.copy().map(Some),
+            d => Ok(d),
+        }
+    }
+
+    pub fn set_decoder(rb_self: Obj<Self>, decoder: &RbDecoder) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        rb_self.tokenizer_mut()?.with_decoder(decoder.clone());
+        Ok(())
+    }
+
+    pub fn set_pre_tokenizer(rb_self: Obj<Self>, pretok: &RbPreTokenizer) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        rb_self.tokenizer_mut()?.with_pre_tokenizer(pretok.clone());
+        Ok(())
+    }
+
+    pub fn set_post_processor(rb_self: Obj<Self>, processor: &RbPostProcessor) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        rb_self.tokenizer_mut()?.with_post_processor(processor.clone());
+        Ok(())
+    }
+
+    pub fn set_normalizer(rb_self: Obj<Self>, normalizer: &RbNormalizer) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        rb_self.tokenizer_mut()?.with_normalizer(normalizer.clone());
+        Ok(())
+    }
+
+    pub fn token_to_id(&self, token: String) -> RbResult<Option<u32>> {
+        Ok(self.tokenizer()?.token_to_id(&token))
+    }
+
+    pub fn id_to_token(&self, id: u32) -> RbResult<Option<String>> {
+        Ok(self.tokenizer()?.id_to_token(id))
     }
 
     // TODO support more kwargs
-    pub fn enable_padding(&self, kwargs: RHash) -> RbResult<()> {
+    pub fn enable_padding(rb_self: Obj<Self>, kwargs: RHash) -> RbResult<()> {
+        rb_self.check_frozen()?;
         let mut params = PaddingParams::default();
 
         let value: Value = kwargs.delete(Symbol::new("direction"))?;
@@ -391,21 +1266,22 @@ impl RbTokenizer {
         }
 
         if !kwargs.is_empty() {
-            // TODO improve message
-            return Err(Error::new(exception::arg_error(), "unknown keyword"));
+            return Err(RbError::unknown_keywords(kwargs));
         }
 
-        self.tokenizer.borrow_mut().with_padding(Some(params));
+        rb_self.tokenizer_mut()?.with_padding(Some(params));
 
         Ok(())
     }
 
-    pub fn no_padding(&self) {
-        self.tokenizer.borrow_mut().with_padding(None);
+    pub fn no_padding(rb_self: Obj<Self>) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        rb_self.tokenizer_mut()?.with_padding(None);
+        Ok(())
     }
 
     pub fn padding(&self) -> RbResult<Option<RHash>> {
-        self.tokenizer.borrow().get_padding().map_or(Ok(None), |params| {
+        self.tokenizer()?.get_padding().map_or(Ok(None), |params| {
             let ret_hash = RHash::new();
 
             ret_hash.aset(
@@ -425,7 +1301,8 @@ impl RbTokenizer {
         })
     }
 
-    pub fn enable_truncation(&self, max_length: usize, kwargs: RHash) -> RbResult<()> {
+    pub fn enable_truncation(rb_self: Obj<Self>, max_length: usize, kwargs: RHash) -> RbResult<()> {
+        rb_self.check_frozen()?;
         let mut params = TruncationParams {
             max_length,
             ..Default::default()
@@ -458,26 +1335,27 @@ impl RbTokenizer {
         }
 
         if !kwargs.is_empty() {
-            // TODO improve message
-            return Err(Error::new(exception::arg_error(), "unknown keyword"));
+            return Err(RbError::unknown_keywords(kwargs));
         }
 
-        if let Err(error_message) = self.tokenizer.borrow_mut().with_truncation(Some(params)) {
+        if let Err(error_message) = rb_self.tokenizer_mut()?.with_truncation(Some(params)) {
             return Err(Error::new(exception::arg_error(), error_message.to_string()));
         }
 
         Ok(())
     }
 
-    pub fn no_truncation(&self) {
-        self.tokenizer
-            .borrow_mut()
+    pub fn no_truncation(rb_self: Obj<Self>) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        rb_self
+            .tokenizer_mut()?
             .with_truncation(None)
             .expect("Failed to set truncation to `None`! This should never happen");
+        Ok(())
     }
 
     pub fn truncation(&self) -> RbResult<Option<RHash>> {
-        self.tokenizer.borrow().get_truncation().map_or(Ok(None), |params| {
+        self.tokenizer()?.get_truncation().map_or(Ok(None), |params| {
             let ret_hash = RHash::new();
 
             ret_hash.aset("max_length", params.max_length)?;
@@ -489,18 +1367,96 @@ impl RbTokenizer {
         })
     }
 
-    pub fn num_special_tokens_to_add(&self, is_pair: bool) -> usize {
-        self.tokenizer
-            .borrow()
+    pub fn num_special_tokens_to_add(&self, is_pair: bool) -> RbResult<usize> {
+        Ok(self
+            .tokenizer()?
             .get_post_processor()
-            .map_or(0, |p| p.added_tokens(is_pair))
+            .map_or(0, |p| p.added_tokens(is_pair)))
     }
 
-    pub fn vocab(&self, with_added_tokens: bool) -> HashMap<String, u32> {
-        self.tokenizer.borrow().get_vocab(with_added_tokens)
+    pub fn vocab(&self, with_added_tokens: bool) -> RbResult<HashMap<String, u32>> {
+        Ok(self.tokenizer()?.get_vocab(with_added_tokens))
     }
 
-    pub fn vocab_size(&self, with_added_tokens: bool) -> usize {
-        self.tokenizer.borrow().get_vocab_size(with_added_tokens)
+    pub fn vocab_size(&self, with_added_tokens: bool) -> RbResult<usize> {
+        Ok(self.tokenizer()?.get_vocab_size(with_added_tokens))
+    }
+
+    pub fn added_tokens_decoder(&self) -> RbResult<RHash> {
+        let hash = RHash::new();
+        for (id, token) in sorted_added_tokens(&self.tokenizer()?) {
+            hash.aset(id, RbAddedToken::from(token))?;
+        }
+        Ok(hash)
+    }
+
+    pub fn special_tokens(&self) -> RbResult<Vec<String>> {
+        Ok(sorted_added_tokens(&self.tokenizer()?)
+            .into_iter()
+            .filter(|(_, token)| token.special)
+            .map(|(_, token)| token.content)
+            .collect())
+    }
+
+    pub fn special_token_ids(&self) -> RbResult<Vec<u32>> {
+        Ok(sorted_added_tokens(&self.tokenizer()?)
+            .into_iter()
+            .filter(|(_, token)| token.special)
+            .map(|(id, _)| id)
+            .collect())
+    }
+
+    pub fn remove_added_tokens(rb_self: Obj<Self>, tokens: RArray) -> RbResult<usize> {
+        rb_self.check_frozen()?;
+        let contents = RbAddedToken::tokens_from_array(tokens, false)?
+            .into_iter()
+            .map(|token| token.content)
+            .collect::<Vec<_>>();
+
+        let mut tokenizer = rb_self.tokenizer_mut()?;
+        let added_tokens = sorted_added_tokens(&tokenizer);
+        let count = added_tokens.len();
+        let added_tokens = added_tokens
+            .into_iter()
+            .filter(|(_, token)| !contents.contains(&token.content))
+            .collect::<Vec<_>>();
+        let removed = count - added_tokens.len();
+        if removed > 0 {
+            *tokenizer = rebuild_added_tokens(&tokenizer, &added_tokens)?.ok_or_else(|| {
+                Error::new(
+                    exception::arg_error(),
+                    "Cannot remove added tokens without changing the ids of other added tokens",
+                )
+            })?;
+        }
+        Ok(removed)
+    }
+
+    pub fn rename_added_token(rb_self: Obj<Self>, old_content: String, new_content: String) -> RbResult<()> {
+        rb_self.check_frozen()?;
+        let mut tokenizer = rb_self.tokenizer_mut()?;
+        let mut added_tokens = sorted_added_tokens(&tokenizer);
+        if added_tokens.iter().any(|(_, token)| token.content == new_content) {
+            return Err(Error::new(
+                exception::arg_error(),
+                format!("Added token already exists: {}", new_content),
+            ));
+        }
+        match added_tokens.iter_mut().find(|(_, token)| token.content == old_content) {
+            Some((_, token)) => token.content = new_content,
+            None => {
+                return Err(Error::new(
+                    exception::arg_error(),
+                    format!("Added token not found: {}", old_content),
+                ))
+            }
+        }
+        *tokenizer = rebuild_added_tokens(&tokenizer, &added_tokens)?.ok_or_else(|| {
+            Error::new(
+                exception::arg_error(),
+                "Cannot rename added token without changing its id",
+            )
+        })?;
+        Ok(())
     }
 }
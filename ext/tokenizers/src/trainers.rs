@@ -12,7 +12,8 @@ use serde::{Deserialize, Serialize};
 use tk::models::TrainerWrapper;
 use tk::Trainer;
 
-use super::{RbResult, TRAINERS};
+use super::utils::{deep_copy, dump, load};
+use super::{RbError, RbResult, TRAINERS};
 
 #[derive(DataTypeFunctions, Clone, Deserialize, Serialize)]
 pub struct RbTrainer {
@@ -68,6 +69,18 @@ macro_rules! setter {
 }
 
 impl RbTrainer {
+    pub fn copy(&self) -> RbResult<Self> {
+        deep_copy(self)
+    }
+
+    pub fn dump(&self, _level: i64) -> RbResult<String> {
+        dump(self)
+    }
+
+    pub fn load(json: String) -> RbResult<Self> {
+        load(&json)
+    }
+
 
     fn bpe_trainer_vocab_size(&self) -> usize {
         getter!(self, BpeTrainer, vocab_size)
@@ -109,16 +122,7 @@ impl RbTrainer {
             self,
             BpeTrainer,
             special_tokens,
-            special_tokens
-                .each()
-                .map(|token| {
-                    if let Ok(content) = String::try_convert(token?) {
-                        Ok(RbAddedToken::from(content, Some(true)).get_token())
-                    } else {
-                        todo!()
-                    }
-                })
-                .collect::<RbResult<Vec<_>>>()?
+            RbAddedToken::tokens_from_array(special_tokens, true)?
         );
         Ok(())
     }
@@ -196,16 +200,7 @@ impl RbTrainer {
             self,
             UnigramTrainer,
             special_tokens,
-            special_tokens
-                .each()
-                .map(|token| {
-                    if let Ok(content) = String::try_convert(token?) {
-                        Ok(RbAddedToken::from(content, Some(true)).get_token())
-                    } else {
-                        todo!()
-                    }
-                })
-                .collect::<RbResult<Vec<_>>>()?
+            RbAddedToken::tokens_from_array(special_tokens, true)?
         );
         Ok(())
     }
@@ -267,16 +262,7 @@ impl RbTrainer {
             self,
             WordLevelTrainer,
             special_tokens,
-            special_tokens
-                .each()
-                .map(|token| {
-                    if let Ok(content) = String::try_convert(token?) {
-                        Ok(RbAddedToken::from(content, Some(true)).get_token())
-                    } else {
-                        todo!()
-                    }
-                })
-                .collect::<RbResult<Vec<_>>>()?
+            RbAddedToken::tokens_from_array(special_tokens, true)?
         );
         Ok(())
     }
@@ -321,16 +307,7 @@ impl RbTrainer {
             self,
             WordPieceTrainer,
             @set_special_tokens,
-            special_tokens
-                .each()
-                .map(|token| {
-                    if let Ok(content) = String::try_convert(token?) {
-                        Ok(RbAddedToken::from(content, Some(true)).get_token())
-                    } else {
-                        todo!()
-                    }
-                })
-                .collect::<RbResult<Vec<_>>>()?
+            RbAddedToken::tokens_from_array(special_tokens, true)?
         );
         Ok(())
     }
@@ -397,16 +374,7 @@ impl RbBpeTrainer {
         let value: Value = kwargs.delete(Symbol::new("special_tokens"))?;
         if !value.is_nil() {
             builder = builder.special_tokens(
-                RArray::try_convert(value)?
-                    .each()
-                    .map(|token| {
-                        if let Ok(content) = String::try_convert(token?) {
-                            Ok(RbAddedToken::from(content, Some(true)).get_token())
-                        } else {
-                            todo!()
-                        }
-                    })
-                    .collect::<RbResult<Vec<_>>>()?,
+                RbAddedToken::tokens_from_array(RArray::try_convert(value)?, true)?,
             );
         }
 
@@ -448,8 +416,7 @@ impl RbBpeTrainer {
         }
 
         if !kwargs.is_empty() {
-            // TODO improve message
-            return Err(Error::new(exception::arg_error(), "unknown keyword"));
+            return Err(RbError::unknown_keywords(kwargs));
         }
 
         Ok(builder.build().into())
@@ -465,16 +432,7 @@ impl RbUnigramTrainer {
         let value: Value = kwargs.delete(Symbol::new("special_tokens"))?;
         if !value.is_nil() {
             builder.special_tokens(
-                RArray::try_convert(value)?
-                    .each()
-                    .map(|token| {
-                        if let Ok(content) = String::try_convert(token?) {
-                            Ok(RbAddedToken::from(content, Some(true)).get_token())
-                        } else {
-                            todo!()
-                        }
-                    })
-                    .collect::<RbResult<Vec<_>>>()?,
+                RbAddedToken::tokens_from_array(RArray::try_convert(value)?, true)?,
             );
         }
 
@@ -521,8 +479,7 @@ impl RbUnigramTrainer {
         }
 
         if !kwargs.is_empty() {
-            // TODO improve message
-            return Err(Error::new(exception::arg_error(), "unknown keyword"));
+            return Err(RbError::unknown_keywords(kwargs));
         }
 
         let trainer = builder.build().map_err(|_| { Error::new(exception::arg_error(), "Cannot build UnigramTrainer") })?;
@@ -539,16 +496,7 @@ impl RbWordLevelTrainer {
         let value: Value = kwargs.delete(Symbol::new("special_tokens"))?;
         if !value.is_nil() {
             builder.special_tokens(
-                RArray::try_convert(value)?
-                    .each()
-                    .map(|token| {
-                        if let Ok(content) = String::try_convert(token?) {
-                            Ok(RbAddedToken::from(content, Some(true)).get_token())
-                        } else {
-                            todo!()
-                        }
-                    })
-                    .collect::<RbResult<Vec<_>>>()?,
+                RbAddedToken::tokens_from_array(RArray::try_convert(value)?, true)?,
             );
         }
 
@@ -580,16 +528,7 @@ impl RbWordPieceTrainer {
         let value: Value = kwargs.delete(Symbol::new("special_tokens"))?;
         if !value.is_nil() {
             builder = builder.special_tokens(
-                RArray::try_convert(value)?
-                    .each()
-                    .map(|token| {
-                        if let Ok(content) = String::try_convert(token?) {
-                            Ok(RbAddedToken::from(content, Some(true)).get_token())
-                        } else {
-                            todo!()
-                        }
-                    })
-                    .collect::<RbResult<Vec<_>>>()?,
+                RbAddedToken::tokens_from_array(RArray::try_convert(value)?, true)?,
             );
         }
 
@@ -631,8 +570,7 @@ impl RbWordPieceTrainer {
         }
 
         if !kwargs.is_empty() {
-            // TODO improve message
-            return Err(Error::new(exception::arg_error(), "unknown keyword"));
+            return Err(RbError::unknown_keywords(kwargs));
         }
 
         Ok(builder.build().into())
@@ -686,6 +624,9 @@ unsafe impl TypedData for RbTrainer {
 
 pub fn init_trainers(ruby: &Ruby, module: &RModule) -> RbResult<()> {
     let trainer = module.define_class("Trainer", ruby.class_object())?;
+    trainer.define_method("_copy", method!(RbTrainer::copy, 0))?;
+    trainer.define_method("_dump", method!(RbTrainer::dump, 1))?;
+    trainer.define_singleton_method("_load", function!(RbTrainer::load, 1))?;
 
     let class = module.define_class("BpeTrainer", trainer)?;
     class.define_singleton_method("_new", function!(RbBpeTrainer::new, 1))?;
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use tk::normalizer::Range;
+use tk::{AddedToken, NormalizedString, Normalizer, Offsets, PreTokenizedString, Token};
+
+// raised when a disallowed special token is found in the input
+// converted to DisallowedSpecialTokenError
+#[derive(Debug)]
+pub struct DisallowedSpecialToken {
+    pub token: String,
+    pub token_id: u32,
+}
+
+impl fmt::Display for DisallowedSpecialToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Encountered disallowed special token: {}", self.token)
+    }
+}
+
+impl std::error::Error for DisallowedSpecialToken {}
+
+// what to do with an added token found in the input
+#[derive(Clone, Copy, PartialEq)]
+pub enum AddedTokenAction {
+    Match,
+    Disallow,
+}
+
+type MatchingSet = (AhoCorasick, Vec<u32>);
+
+// splits added tokens from the input the same way as the added vocabulary of the tokenizer,
+// but only matches the tokens it's given, so the tokens to match can change for each call
+// without modifying the tokenizer
+// tokens that aren't given are encoded as plain text
+pub struct AddedTokenSplitter {
+    tokens: HashMap<u32, (AddedToken, AddedTokenAction)>,
+    split_trie: MatchingSet,
+    split_normalized_trie: MatchingSet,
+}
+
+impl AddedTokenSplitter {
+    pub fn new<N: Normalizer>(
+        tokens: Vec<(u32, AddedToken, AddedTokenAction)>,
+        normalizer: Option<&N>,
+    ) -> tk::Result<Self> {
+        let mut patterns = Vec::new();
+        let mut ids = Vec::new();
+        let mut normalized_patterns = Vec::new();
+        let mut normalized_ids = Vec::new();
+        for (id, token, _) in &tokens {
+            if token.normalized {
+                let mut content = NormalizedString::from(token.content.as_str());
+                if let Some(n) = normalizer {
+                    n.normalize(&mut content)?;
+                }
+                normalized_patterns.push(content.get().to_string());
+                normalized_ids.push(*id);
+            } else {
+                patterns.push(token.content.clone());
+                ids.push(*id);
+            }
+        }
+
+        Ok(Self {
+            tokens: tokens
+                .into_iter()
+                .map(|(id, token, action)| (id, (token, action)))
+                .collect(),
+            split_trie: (build_trie(&patterns)?, ids),
+            split_normalized_trie: (build_trie(&normalized_patterns)?, normalized_ids),
+        })
+    }
+
+    // same as extract_and_normalize in tokenizers
+    pub fn extract_and_normalize<N: Normalizer>(
+        &self,
+        normalizer: Option<&N>,
+        sequence: &str,
+    ) -> tk::Result<PreTokenizedString> {
+        let mut pretokenized: PreTokenizedString = sequence.into();
+        pretokenized.split(|_, sequence| self.split_with_indices(sequence, &self.split_trie))?;
+        pretokenized.split(|_, mut sequence| {
+            if let Some(n) = normalizer {
+                n.normalize(&mut sequence)?;
+            }
+            self.split_with_indices(sequence, &self.split_normalized_trie)
+        })?;
+        Ok(pretokenized)
+    }
+
+    fn find_matches(&self, sentence: &str, split_re: &MatchingSet) -> tk::Result<Vec<(Option<u32>, Offsets)>> {
+        if sentence.is_empty() {
+            return Ok(vec![(None, (0, 0))]);
+        }
+
+        let mut start_offset = 0;
+        let mut splits = vec![];
+
+        for mat in split_re.0.find_iter(sentence) {
+            let mut start = mat.start();
+            let mut stop = mat.end();
+            let id = split_re.1[mat.pattern()];
+            let (token, action) = &self.tokens[&id];
+
+            if token.single_word {
+                let start_space = start == 0 || !ends_with_word(&sentence[..start]);
+                let stop_space = stop == sentence.len() || !starts_with_word(&sentence[stop..]);
+                if !stop_space || !start_space {
+                    continue;
+                }
+            }
+            if *action == AddedTokenAction::Disallow {
+                return Err(Box::new(DisallowedSpecialToken {
+                    token: token.content.clone(),
+                    token_id: id,
+                }));
+            }
+            if token.lstrip {
+                // the previous match may have already matched the spaces
+                start = std::cmp::max(space_leftmost_at_end(&sentence[..start]), start_offset);
+            }
+            if token.rstrip {
+                stop += space_rightmost_at_start(&sentence[stop..]);
+            }
+            if start_offset < start {
+                splits.push((None, (start_offset, start)));
+            }
+            splits.push((Some(id), (start, stop)));
+            start_offset = stop;
+        }
+
+        if start_offset != sentence.len() {
+            splits.push((None, (start_offset, sentence.len())));
+        }
+
+        Ok(splits)
+    }
+
+    fn split_with_indices(
+        &self,
+        sentence: NormalizedString,
+        split_re: &MatchingSet,
+    ) -> tk::Result<Vec<(NormalizedString, Option<Vec<Token>>)>> {
+        self.find_matches(sentence.get(), split_re)?
+            .into_iter()
+            .map(|(id, offsets)| {
+                let slice = sentence
+                    .slice(Range::Normalized(offsets.0..offsets.1))
+                    .ok_or("AddedVocabulary bad split")?;
+                let tokens = id.map(|id| {
+                    let value = slice.get().to_owned();
+                    let len = value.len();
+                    vec![Token::new(id, value, (0, len))]
+                });
+                Ok((slice, tokens))
+            })
+            .collect()
+    }
+}
+
+fn build_trie(patterns: &[String]) -> tk::Result<AhoCorasick> {
+    Ok(AhoCorasickBuilder::new()
+        .match_kind(MatchKind::LeftmostLongest)
+        .build(patterns)?)
+}
+
+// same as \w
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn ends_with_word(sentence: &str) -> bool {
+    sentence.chars().next_back().is_some_and(is_word_char)
+}
+
+fn starts_with_word(sentence: &str) -> bool {
+    sentence.chars().next().is_some_and(is_word_char)
+}
+
+fn space_leftmost_at_end(sentence: &str) -> usize {
+    sentence.trim_end().len()
+}
+
+fn space_rightmost_at_start(sentence: &str) -> usize {
+    sentence.len() - sentence.trim_start().len()
+}
@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+use magnus::{exception, Error, TryConvert, Value};
+
+use crate::RbResult;
+
+// closing punctuation allowed after the end of a sentence
+const SENTENCE_CLOSERS: &[char] = &['"', '\'', ')', ']', '\u{201d}', '\u{2019}'];
+const SENTENCE_TERMINATORS: &[char] = &['.', '!', '?', '\u{3002}', '\u{ff01}', '\u{ff1f}'];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChunkBoundary {
+    Word,
+    Sentence,
+}
+
+impl TryConvert for ChunkBoundary {
+    fn try_convert(ob: Value) -> RbResult<Self> {
+        let value = String::try_convert(ob)?;
+        match value.as_str() {
+            "word" => Ok(ChunkBoundary::Word),
+            "sentence" => Ok(ChunkBoundary::Sentence),
+            _ => Err(Error::new(exception::arg_error(), "The respect value must be 'word' or 'sentence'")),
+        }
+    }
+}
+
+pub struct Chunk {
+    // byte range in the text
+    pub bytes: Range<usize>,
+    // char range in the text
+    pub chars: Range<usize>,
+    pub token_count: usize,
+}
+
+// splits an encoding without special tokens into chunks of at most max_tokens tokens
+// chunks only end at word (or sentence) boundaries, so a single word longer than
+// max_tokens becomes its own chunk
+// consecutive chunks share whole words totaling at most overlap tokens
+pub fn chunk_encoding(
+    text: &str,
+    encoding: &tk::Encoding,
+    max_tokens: usize,
+    overlap: usize,
+    boundary: ChunkBoundary,
+) -> Vec<Chunk> {
+    let offsets = encoding.get_offsets();
+    let word_ids = encoding.get_word_ids();
+
+    // token ranges that can't be split
+    let mut words = Vec::new();
+    let mut sentence_ends = Vec::new();
+    let mut start = 0;
+    for i in 1..=offsets.len() {
+        if i < offsets.len() && !is_word_boundary(text, offsets, word_ids, i) {
+            continue;
+        }
+        words.push(start..i);
+        sentence_ends.push(i == offsets.len() || is_sentence_boundary(text, offsets[i].0));
+        start = i;
+    }
+
+    // sentences that don't fit are split into words, which are grouped by sentence
+    // so they aren't combined with other sentences
+    let mut units: Vec<(Range<usize>, Option<usize>)> = Vec::new();
+    match boundary {
+        ChunkBoundary::Word => units.extend(words.into_iter().map(|w| (w, None))),
+        ChunkBoundary::Sentence => {
+            let mut sentence: Vec<Range<usize>> = Vec::new();
+            for (word, end) in words.into_iter().zip(sentence_ends) {
+                sentence.push(word);
+                if end {
+                    let tokens = sentence[0].start..sentence[sentence.len() - 1].end;
+                    if tokens.len() > max_tokens {
+                        let group = Some(units.len());
+                        units.extend(sentence.drain(..).map(|w| (w, group)));
+                    } else {
+                        units.push((tokens, None));
+                        sentence.clear();
+                    }
+                }
+            }
+        }
+    }
+
+    let char_starts = text.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+    let char_index = |byte: usize| char_starts.partition_point(|&i| i < byte);
+
+    let mut chunks = Vec::new();
+    let mut first = 0;
+    while first < units.len() {
+        let mut last = first;
+        while last + 1 < units.len()
+            && units[last + 1].1 == units[last].1
+            && units[last + 1].0.end - units[first].0.start <= max_tokens
+        {
+            last += 1;
+        }
+
+        let tokens = units[first].0.start..units[last].0.end;
+        let start = floor_char_boundary(text, offsets[tokens.start].0);
+        let end = ceil_char_boundary(text, offsets[tokens.end - 1].1.max(offsets[tokens.start].0));
+        chunks.push(Chunk {
+            bytes: start..end,
+            chars: char_index(start)..char_index(end),
+            token_count: tokens.len(),
+        });
+
+        if last + 1 == units.len() {
+            break;
+        }
+
+        // back up by whole units that can be combined with the next unit,
+        // but always move forward
+        let mut next = last + 1;
+        while next - 1 > first
+            && units[next - 1].1 == units[last + 1].1
+            && units[last].0.end - units[next - 1].0.start <= overlap
+        {
+            next -= 1;
+        }
+        first = next;
+    }
+    chunks
+}
+
+fn is_word_boundary(text: &str, offsets: &[(usize, usize)], word_ids: &[Option<u32>], i: usize) -> bool {
+    let start = offsets[i].0;
+    // tokens that share bytes are parts of the same char
+    if start < offsets[i - 1].1 || !text.is_char_boundary(start) {
+        return false;
+    }
+    if word_ids[i] != word_ids[i - 1] {
+        return true;
+    }
+    // some pre-tokenizers keep whitespace in words
+    text[..start].ends_with(char::is_whitespace) || text[start..].starts_with(char::is_whitespace)
+}
+
+fn is_sentence_boundary(text: &str, start: usize) -> bool {
+    let before = &text[..start];
+    let trimmed = before.trim_end();
+    let whitespace = &before[trimmed.len()..];
+    let whitespace = if whitespace.is_empty() {
+        let after = &text[start..];
+        &after[..after.len() - after.trim_start().len()]
+    } else {
+        whitespace
+    };
+    if whitespace.is_empty() {
+        return false;
+    }
+    whitespace.contains('\n') || trimmed.trim_end_matches(SENTENCE_CLOSERS).ends_with(SENTENCE_TERMINATORS)
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
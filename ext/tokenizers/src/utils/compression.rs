@@ -0,0 +1,35 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// detect compression from magic bytes, since JSON can never start with them
+pub fn decompress(bytes: Vec<u8>) -> tk::Result<Vec<u8>> {
+    if bytes.starts_with(GZIP_MAGIC) {
+        let mut decoded = Vec::new();
+        GzDecoder::new(&bytes[..]).read_to_end(&mut decoded)?;
+        Ok(decoded)
+    } else if bytes.starts_with(ZSTD_MAGIC) {
+        Ok(zstd::stream::decode_all(&bytes[..])?)
+    } else {
+        Ok(bytes)
+    }
+}
+
+// compress based on the file extension
+pub fn compress<P: AsRef<Path>>(bytes: Vec<u8>, path: P) -> tk::Result<Vec<u8>> {
+    match path.as_ref().extension().and_then(|e| e.to_str()) {
+        Some("gz") => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&bytes)?;
+            Ok(encoder.finish()?)
+        }
+        Some("zst") => Ok(zstd::stream::encode_all(&bytes[..], 0)?),
+        _ => Ok(bytes),
+    }
+}
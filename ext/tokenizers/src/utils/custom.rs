@@ -0,0 +1,92 @@
+use std::cell::Cell;
+use std::fmt;
+use std::sync::Arc;
+
+use magnus::{prelude::*, value::BoxValue, ArgList, RClass, Ruby, Value};
+
+use crate::{RbError, RbResult};
+
+// a Ruby object used by a custom component
+// it's only called while holding the GVL, since tokenizers with custom components don't release it
+#[derive(Clone)]
+pub struct RbCustomObject {
+    object: Arc<BoxValue<Value>>,
+    // None for the main Ractor, so most calls don't need to look up the current Ractor
+    ractor: Option<Arc<BoxValue<Value>>>,
+}
+
+unsafe impl Send for RbCustomObject {}
+unsafe impl Sync for RbCustomObject {}
+
+impl RbCustomObject {
+    pub fn new(object: Value) -> RbResult<Self> {
+        let ractor = if is_main_ractor() {
+            None
+        } else {
+            Some(Arc::new(BoxValue::new(current_ractor()?)))
+        };
+        Ok(Self {
+            object: Arc::new(BoxValue::new(object)),
+            ractor,
+        })
+    }
+
+    pub fn call<A>(&self, method: &str, args: A) -> tk::Result<Value>
+    where
+        A: ArgList,
+    {
+        // frozen tokenizers can be shared, but the object can only be used by its own Ractor
+        let same_ractor = match &self.ractor {
+            None => is_main_ractor(),
+            Some(ractor) => {
+                !is_main_ractor() && current_ractor().and_then(|r| r.equal(***ractor)).map_err(RbError::callback)?
+            }
+        };
+        if !same_ractor {
+            return Err("Custom components can only be used in the Ractor they were created in".into());
+        }
+        with_callback(|| self.object.funcall(method, args)).map_err(RbError::callback)
+    }
+}
+
+impl fmt::Debug for RbCustomObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RbCustomObject")
+    }
+}
+
+fn is_main_ractor() -> bool {
+    unsafe { rb_sys::rb_ractor_main_p_() }
+}
+
+fn current_ractor() -> RbResult<Value> {
+    let ruby = Ruby::get().unwrap();
+    ruby.class_object().const_get::<_, RClass>("Ractor")?.funcall("current", ())
+}
+
+thread_local! {
+    static CALLBACK_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+struct CallbackGuard;
+
+impl Drop for CallbackGuard {
+    fn drop(&mut self) {
+        CALLBACK_DEPTH.with(|d| d.set(d.get() - 1));
+    }
+}
+
+// marks Ruby code called while a tokenizer lock may be held by this thread,
+// like custom components and enumerators used for training
+pub fn with_callback<F, R>(func: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    CALLBACK_DEPTH.with(|d| d.set(d.get() + 1));
+    let _guard = CallbackGuard;
+    func()
+}
+
+pub fn in_callback() -> bool {
+    CALLBACK_DEPTH.with(|d| d.get() > 0)
+}
@@ -0,0 +1,90 @@
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::RbResult;
+
+struct NoGvlCall<F, R> {
+    func: Option<F>,
+    result: Option<std::thread::Result<R>>,
+}
+
+unsafe extern "C" fn call_without_gvl<F, R>(data: *mut c_void) -> *mut c_void
+where
+    F: FnOnce() -> R,
+{
+    let call = &mut *(data as *mut NoGvlCall<F, R>);
+    let func = call.func.take().unwrap();
+    // panics must not unwind through Ruby's C frames
+    call.result = Some(panic::catch_unwind(AssertUnwindSafe(func)));
+    ptr::null_mut()
+}
+
+// runs func without holding the GVL so other Ruby threads can make progress
+// func must not touch any Ruby objects, so convert inputs to owned Rust data first
+pub fn nogvl<F, R>(func: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    call_nogvl(func, None, ptr::null_mut())
+}
+
+unsafe extern "C" fn cancel(data: *mut c_void) {
+    let cancelled = &*(data as *const AtomicBool);
+    cancelled.store(true, Ordering::SeqCst);
+}
+
+// same as nogvl, but sets cancelled when Ruby interrupts the thread (Ctrl-C, Thread#raise, Thread#kill)
+// func should stop early once it's set, and interrupts should be checked after it returns
+pub fn nogvl_cancelable<F, R>(cancelled: &AtomicBool, func: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    call_nogvl(func, Some(cancel), cancelled as *const AtomicBool as *mut c_void)
+}
+
+fn call_nogvl<F, R>(func: F, ubf: rb_sys::rb_unblock_function_t, ubf_data: *mut c_void) -> R
+where
+    F: FnOnce() -> R,
+{
+    let mut call = NoGvlCall {
+        func: Some(func),
+        result: None,
+    };
+
+    unsafe {
+        rb_sys::rb_thread_call_without_gvl(
+            Some(call_without_gvl::<F, R>),
+            &mut call as *mut NoGvlCall<F, R> as *mut c_void,
+            ubf,
+            ubf_data,
+        );
+    }
+
+    match call.result.unwrap() {
+        Ok(v) => v,
+        Err(e) => panic::resume_unwind(e),
+    }
+}
+
+// raises pending interrupts (Ctrl-C, Thread#raise, Thread#kill) for the current thread
+pub fn check_interrupts() -> RbResult<()> {
+    magnus::rb_sys::protect(|| {
+        unsafe { rb_sys::rb_thread_check_ints() };
+        rb_sys::Qnil as rb_sys::VALUE
+    })
+    .map(|_| ())
+}
+
+// only releases the GVL when release is true, for work that may need to call into Ruby
+pub fn maybe_nogvl<F, R>(release: bool, func: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    if release {
+        nogvl(func)
+    } else {
+        func()
+    }
+}
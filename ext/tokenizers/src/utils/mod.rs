@@ -1,5 +1,23 @@
+mod added_tokens;
+mod chunking;
+mod compression;
+mod custom;
+mod gvl;
 mod normalization;
+mod offsets;
+mod pretokenization;
+mod ref_mut;
 mod regex;
+mod serialization;
 
+pub use added_tokens::*;
+pub use chunking::*;
+pub use compression::*;
+pub use custom::*;
+pub use gvl::*;
 pub use normalization::*;
+pub use offsets::*;
+pub use pretokenization::*;
+pub use ref_mut::*;
 pub use regex::*;
+pub use serialization::*;
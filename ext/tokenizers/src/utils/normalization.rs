@@ -1,9 +1,15 @@
+use std::cell::RefCell;
+
+use super::ref_mut::RefMutContainer;
 use super::regex::{regex, RbRegex};
-use crate::RbResult;
+use crate::{RbError, RbResult};
+use magnus::block::block_proc;
 use magnus::prelude::*;
-use magnus::{exception, Error, TryConvert, Value};
-use tk::normalizer::SplitDelimiterBehavior;
+use magnus::{exception, Error, IntoValue, RArray, Range, TryConvert, Value};
+use serde::Deserialize;
+use tk::normalizer::{char_to_bytes, Range as NormalizerRange, SplitDelimiterBehavior};
 use tk::pattern::Pattern;
+use tk::NormalizedString;
 
 #[derive(Clone)]
 pub enum RbPattern<'p> {
@@ -32,9 +38,7 @@ impl Pattern for RbPattern<'_> {
                     s.find_matches(inside)
                 }
             }
-            RbPattern::Regex(_r) => {
-                todo!()
-            }
+            RbPattern::Regex(r) => (&r.inner).find_matches(inside),
         }
     }
 }
@@ -43,7 +47,7 @@ impl From<RbPattern<'_>> for tk::normalizers::replace::ReplacePattern {
     fn from(pattern: RbPattern<'_>) -> Self {
         match pattern {
             RbPattern::Str(s) => Self::String(s),
-            RbPattern::Regex(_r) => todo!(),
+            RbPattern::Regex(r) => Self::Regex(r.pattern.clone()),
         }
     }
 }
@@ -52,7 +56,24 @@ impl From<RbPattern<'_>> for tk::pre_tokenizers::split::SplitPattern {
     fn from(pattern: RbPattern<'_>) -> Self {
         match pattern {
             RbPattern::Str(s) => Self::String(s),
-            RbPattern::Regex(_r) => todo!(),
+            RbPattern::Regex(r) => Self::Regex(r.pattern.clone()),
+        }
+    }
+}
+
+// serialized form of SplitPattern and ReplacePattern
+#[derive(Deserialize)]
+pub enum SerializedPattern {
+    String(String),
+    Regex(String),
+}
+
+impl SerializedPattern {
+    // regexes are returned as Tokenizers::Regex
+    pub fn into_ruby(self) -> RbResult<Value> {
+        match self {
+            SerializedPattern::String(s) => Ok(s.into_value()),
+            SerializedPattern::Regex(r) => Ok(RbRegex::new(r)?.into_value()),
         }
     }
 }
@@ -79,8 +100,221 @@ impl TryConvert for RbSplitDelimiterBehavior {
     }
 }
 
+impl RbSplitDelimiterBehavior {
+    pub fn name(&self) -> &'static str {
+        match self.0 {
+            SplitDelimiterBehavior::Removed => "removed",
+            SplitDelimiterBehavior::Isolated => "isolated",
+            SplitDelimiterBehavior::MergedWithPrevious => "merged_with_previous",
+            SplitDelimiterBehavior::MergedWithNext => "merged_with_next",
+            SplitDelimiterBehavior::Contiguous => "contiguous",
+        }
+    }
+}
+
 impl From<RbSplitDelimiterBehavior> for SplitDelimiterBehavior {
     fn from(v: RbSplitDelimiterBehavior) -> Self {
         v.0
     }
 }
+
+// strings passed to custom components are only usable during that call,
+// while the results of split and slice are owned by Ruby
+enum RbNormalizedStringInner {
+    Owned(RefCell<NormalizedString>),
+    RefMut(RefMutContainer<NormalizedString>),
+}
+
+#[magnus::wrap(class = "Tokenizers::NormalizedString")]
+pub struct RbNormalizedString {
+    inner: RbNormalizedStringInner,
+}
+
+impl From<NormalizedString> for RbNormalizedString {
+    fn from(normalized: NormalizedString) -> Self {
+        Self {
+            inner: RbNormalizedStringInner::Owned(RefCell::new(normalized)),
+        }
+    }
+}
+
+impl From<RefMutContainer<NormalizedString>> for RbNormalizedString {
+    fn from(container: RefMutContainer<NormalizedString>) -> Self {
+        Self {
+            inner: RbNormalizedStringInner::RefMut(container),
+        }
+    }
+}
+
+impl RbNormalizedString {
+    fn with<F, U>(&self, f: F) -> RbResult<U>
+    where
+        F: FnOnce(&NormalizedString) -> U,
+    {
+        self.with_mut(|n| f(&*n))
+    }
+
+    fn with_mut<F, U>(&self, f: F) -> RbResult<U>
+    where
+        F: FnOnce(&mut NormalizedString) -> U,
+    {
+        match &self.inner {
+            RbNormalizedStringInner::Owned(n) => {
+                let mut n = n
+                    .try_borrow_mut()
+                    .map_err(|_| RbError::from("Cannot use this object while it's being modified".into()))?;
+                Ok(f(&mut n))
+            }
+            RbNormalizedStringInner::RefMut(n) => n.map_mut(f),
+        }
+    }
+
+    // copies the string so it can be kept by Rust
+    pub(crate) fn to_normalized(&self) -> RbResult<NormalizedString> {
+        self.with(|n| n.clone())
+    }
+
+    pub fn normalized(&self) -> RbResult<String> {
+        self.with(|n| n.get().to_owned())
+    }
+
+    pub fn original(&self) -> RbResult<String> {
+        self.with(|n| n.get_original().to_owned())
+    }
+
+    pub fn nfd(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.nfd();
+        })
+    }
+
+    pub fn nfkd(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.nfkd();
+        })
+    }
+
+    pub fn nfc(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.nfc();
+        })
+    }
+
+    pub fn nfkc(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.nfkc();
+        })
+    }
+
+    pub fn lowercase(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.lowercase();
+        })
+    }
+
+    pub fn uppercase(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.uppercase();
+        })
+    }
+
+    pub fn prepend(&self, s: String) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.prepend(&s);
+        })
+    }
+
+    pub fn append(&self, s: String) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.append(&s);
+        })
+    }
+
+    pub fn lstrip(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.lstrip();
+        })
+    }
+
+    pub fn rstrip(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.rstrip();
+        })
+    }
+
+    pub fn strip(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.strip();
+        })
+    }
+
+    pub fn clear(&self) -> RbResult<()> {
+        self.with_mut(|n| {
+            n.clear();
+        })
+    }
+
+    pub fn replace(&self, pattern: RbPattern, content: String) -> RbResult<()> {
+        self
+            .with_mut(|n| n.replace(pattern, &content))?
+            .map_err(RbError::from)
+    }
+
+    pub fn split(&self, pattern: RbPattern, behavior: RbSplitDelimiterBehavior) -> RbResult<RArray> {
+        let splits = self
+            .with(|n| n.split(pattern, behavior.into()))?
+            .map_err(RbError::from)?;
+        Ok(RArray::from_iter(splits.into_iter().map(RbNormalizedString::from)))
+    }
+
+    // range is in chars of the normalized string
+    pub fn slice(&self, range: Range) -> RbResult<Option<RbNormalizedString>> {
+        self.with(|n| {
+            let (start, len) = range.beg_len(n.get().chars().count())?;
+            Ok(char_to_bytes(n.get(), start..start + len)
+                .and_then(|bytes| n.slice(NormalizerRange::Normalized(bytes)))
+                .map(RbNormalizedString::from))
+        })?
+    }
+
+    // exceptions from the block are raised once all chars have been visited
+    pub fn filter(&self) -> RbResult<()> {
+        let block = block_proc()?;
+        let error = RefCell::new(None);
+        self.with_mut(|n| {
+            n.filter(|c| {
+                if error.borrow().is_some() {
+                    return true;
+                }
+                match block.call::<_, Value>((c,)) {
+                    Ok(v) => v.to_bool(),
+                    Err(e) => {
+                        *error.borrow_mut() = Some(e);
+                        true
+                    }
+                }
+            });
+        })?;
+        error.into_inner().map_or(Ok(()), Err)
+    }
+
+    pub fn map(&self) -> RbResult<()> {
+        let block = block_proc()?;
+        let error = RefCell::new(None);
+        self.with_mut(|n| {
+            n.map(|c| {
+                if error.borrow().is_some() {
+                    return c;
+                }
+                match block.call::<_, char>((c,)) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        *error.borrow_mut() = Some(e);
+                        c
+                    }
+                }
+            });
+        })?;
+        error.into_inner().map_or(Ok(()), Err)
+    }
+}
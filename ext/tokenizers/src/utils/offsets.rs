@@ -0,0 +1,37 @@
+// converts byte offsets to UTF-16 code unit offsets
+// offsets inside a char are extended to the whole char, like char offsets in tokenizers
+pub struct Utf16OffsetConverter {
+    // UTF-16 start and end of the char containing each byte
+    bounds: Vec<(usize, usize)>,
+}
+
+impl Utf16OffsetConverter {
+    pub fn new(sequence: &str) -> Self {
+        let mut bounds = Vec::with_capacity(sequence.len());
+        let mut pos = 0;
+        for c in sequence.chars() {
+            let end = pos + c.len_utf16();
+            bounds.extend(std::iter::repeat((pos, end)).take(c.len_utf8()));
+            pos = end;
+        }
+        Self { bounds }
+    }
+
+    fn position(&self, byte: usize) -> Option<usize> {
+        if byte == self.bounds.len() {
+            Some(self.bounds.last().map_or(0, |b| b.1))
+        } else {
+            self.bounds.get(byte).map(|b| b.0)
+        }
+    }
+
+    pub fn convert(&self, offsets: (usize, usize)) -> Option<(usize, usize)> {
+        let start = self.position(offsets.0)?;
+        let end = if offsets.1 > offsets.0 {
+            self.bounds.get(offsets.1 - 1)?.1
+        } else {
+            start
+        };
+        Some((start, end))
+    }
+}
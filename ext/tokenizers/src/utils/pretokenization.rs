@@ -0,0 +1,113 @@
+use super::normalization::RbNormalizedString;
+use super::ref_mut::RefMutContainer;
+use crate::{RbError, RbResult};
+use magnus::block::block_proc;
+use magnus::{exception, Error, RArray, TryConvert, Value};
+use tk::normalizer::char_to_bytes;
+use tk::{OffsetReferential, OffsetType, PreTokenizedString, Token};
+
+pub struct RbOffsetReferential(OffsetReferential);
+
+impl TryConvert for RbOffsetReferential {
+    fn try_convert(obj: Value) -> RbResult<Self> {
+        let s = String::try_convert(obj)?;
+
+        Ok(Self(match s.as_str() {
+            "original" => Ok(OffsetReferential::Original),
+            "normalized" => Ok(OffsetReferential::Normalized),
+            _ => Err(Error::new(
+                exception::arg_error(),
+                "The offset_referential value must be 'original' or 'normalized'",
+            )),
+        }?))
+    }
+}
+
+pub struct RbOffsetType(OffsetType);
+
+impl TryConvert for RbOffsetType {
+    fn try_convert(obj: Value) -> RbResult<Self> {
+        let s = String::try_convert(obj)?;
+
+        Ok(Self(match s.as_str() {
+            "char" => Ok(OffsetType::Char),
+            "byte" => Ok(OffsetType::Byte),
+            _ => Err(Error::new(
+                exception::arg_error(),
+                "The offset_type value must be 'char' or 'byte'",
+            )),
+        }?))
+    }
+}
+
+// passed to custom pre-tokenizers and only usable during that call
+#[magnus::wrap(class = "Tokenizers::PreTokenizedString")]
+pub struct RbPreTokenizedString {
+    inner: RefMutContainer<PreTokenizedString>,
+}
+
+impl From<RefMutContainer<PreTokenizedString>> for RbPreTokenizedString {
+    fn from(inner: RefMutContainer<PreTokenizedString>) -> Self {
+        Self { inner }
+    }
+}
+
+impl RbPreTokenizedString {
+    // the block is called with the index and NormalizedString of each split
+    // and returns an array of NormalizedStrings to replace it
+    pub fn split(&self) -> RbResult<()> {
+        let block = block_proc()?;
+        self.inner
+            .map_mut(|pretok| {
+                pretok.split(|i, normalized| {
+                    let splits: RArray = block
+                        .call((i, RbNormalizedString::from(normalized)))
+                        .map_err(RbError::callback)?;
+                    splits
+                        .each()
+                        .map(|split| {
+                            let split: &RbNormalizedString = TryConvert::try_convert(split?)?;
+                            split.to_normalized()
+                        })
+                        .collect::<RbResult<Vec<_>>>()
+                        .map_err(RbError::callback)
+                })
+            })?
+            .map_err(RbError::from)
+    }
+
+    pub fn get_splits(
+        &self,
+        offset_referential: RbOffsetReferential,
+        offset_type: RbOffsetType,
+    ) -> RbResult<Vec<(String, (usize, usize))>> {
+        self.inner.map(|pretok| {
+            pretok
+                .get_splits(offset_referential.0, offset_type.0)
+                .into_iter()
+                .map(|(s, o, _)| (s.to_owned(), o))
+                .collect()
+        })
+    }
+
+    // the block is called with the string of each split and returns
+    // an array of [id, value, [start, end]] tokens with char offsets in the split
+    pub fn tokenize(&self) -> RbResult<()> {
+        let block = block_proc()?;
+        self.inner
+            .map_mut(|pretok| {
+                pretok.tokenize(|normalized| {
+                    let tokens: Vec<(u32, String, (usize, usize))> =
+                        block.call((normalized.get(),)).map_err(RbError::callback)?;
+                    tokens
+                        .into_iter()
+                        .map(|(id, value, (start, end))| -> tk::Result<Token> {
+                            let offsets = char_to_bytes(normalized.get(), start..end).ok_or("Invalid token offsets")?;
+                            Ok(Token::new(id, value, (offsets.start, offsets.end)))
+                        })
+                        .collect()
+                })
+            })?
+            .map_err(RbError::from)
+    }
+}
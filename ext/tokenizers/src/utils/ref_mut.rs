@@ -0,0 +1,79 @@
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use crate::{RbError, RbResult};
+
+// gives Ruby access to a value borrowed from Rust for the duration of a call
+// the pointer is cleared when the guard is dropped, so later use raises instead of reading freed memory
+pub struct RefMutContainer<T> {
+    inner: Arc<Mutex<Option<*mut T>>>,
+}
+
+impl<T> RefMutContainer<T> {
+    fn new(content: &mut T) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Some(content as *mut T))),
+        }
+    }
+
+    fn destroy(&self) {
+        let mut lock = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        lock.take();
+    }
+
+    pub fn map<F, U>(&self, f: F) -> RbResult<U>
+    where
+        F: FnOnce(&T) -> U,
+    {
+        self.map_mut(|v| f(&*v))
+    }
+
+    // the lock is only held by the current call, so a nested call (from a block) raises instead of deadlocking
+    pub fn map_mut<F, U>(&self, f: F) -> RbResult<U>
+    where
+        F: FnOnce(&mut T) -> U,
+    {
+        let lock = self
+            .inner
+            .try_lock()
+            .map_err(|_| RbError::from("Cannot use this object while it's being modified".into()))?;
+        let ptr = (*lock)
+            .ok_or_else(|| RbError::from("Cannot use this object outside of the method it was passed to".into()))?;
+        Ok(f(unsafe { &mut *ptr }))
+    }
+}
+
+impl<T> Clone for RefMutContainer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for RefMutContainer<T> {}
+unsafe impl<T: Sync> Sync for RefMutContainer<T> {}
+
+pub struct RefMutGuard<'r, T> {
+    content: RefMutContainer<T>,
+    r: PhantomData<&'r mut T>,
+}
+
+impl<'r, T> RefMutGuard<'r, T> {
+    pub fn new(content: &'r mut T) -> Self {
+        Self {
+            content: RefMutContainer::new(content),
+            r: PhantomData,
+        }
+    }
+
+    pub fn get(&self) -> RefMutContainer<T> {
+        self.content.clone()
+    }
+}
+
+impl<T> Drop for RefMutGuard<'_, T> {
+    fn drop(&mut self) {
+        self.content.destroy()
+    }
+}
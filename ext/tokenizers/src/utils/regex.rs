@@ -1,6 +1,6 @@
 use onig::Regex;
-use magnus::{exception, prelude::*, value::Lazy, Error, RClass, Ruby};
-use crate::{RbResult, TOKENIZERS};
+use magnus::{prelude::*, value::Lazy, RClass, Ruby};
+use crate::{RbError, RbResult, TOKENIZERS};
 
 #[magnus::wrap(class = "Tokenizers::Regex")]
 pub struct RbRegex {
@@ -11,10 +11,14 @@ pub struct RbRegex {
 impl RbRegex {
     pub fn new(s: String) -> RbResult<Self> {
         Ok(Self {
-            inner: Regex::new(&s).map_err(|e| Error::new(exception::runtime_error(), e.description().to_owned()))?,
+            inner: Regex::new(&s).map_err(|e| RbError::regex(e.description().to_owned(), &s))?,
             pattern: s,
         })
     }
+
+    pub fn source(&self) -> String {
+        self.pattern.clone()
+    }
 }
 
 static REGEX: Lazy<RClass> = Lazy::new(|ruby| ruby.get_inner(&TOKENIZERS).const_get("Regex").unwrap());
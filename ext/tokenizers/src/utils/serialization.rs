@@ -0,0 +1,28 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{RbError, RbResult};
+
+// used by _dump and _load for Marshal
+pub fn dump<T: Serialize>(value: &T) -> RbResult<String> {
+    serde_json::to_string(value).map_err(|e| RbError::serialization(e.into()))
+}
+
+pub fn load<T: DeserializeOwned>(json: &str) -> RbResult<T> {
+    serde_json::from_str(json).map_err(|e| RbError::serialization(e.into()))
+}
+
+// for fields that are private upstream
+// serializes the whole component on each call, so avoid it in hot paths
+pub fn serialized_field<S: Serialize, T: DeserializeOwned>(value: &S, name: &str) -> RbResult<T> {
+    serde_json::to_value(value)
+        .and_then(|mut v| serde_json::from_value(v[name].take()))
+        .map_err(|e| RbError::serialization(e.into()))
+}
+
+// round trip through the serde representation
+// so the copy does not share Arc<RwLock<_>> with the original
+pub fn deep_copy<T: Serialize + DeserializeOwned>(value: &T) -> RbResult<T> {
+    serde_json::to_value(value)
+        .and_then(serde_json::from_value)
+        .map_err(|e| RbError::serialization(e.into()))
+}
@@ -32,6 +32,7 @@ require_relative "tokenizers/pre_tokenizers/split"
 
 # processors
 require_relative "tokenizers/processors/byte_level"
+require_relative "tokenizers/processors/post_processor"
 require_relative "tokenizers/processors/roberta_processing"
 require_relative "tokenizers/processors/template_processing"
 
@@ -42,14 +43,65 @@ require_relative "tokenizers/trainers/word_level_trainer"
 require_relative "tokenizers/trainers/word_piece_trainer"
 
 # other
+require_relative "tokenizers/added_token"
+require_relative "tokenizers/batch_encoding"
 require_relative "tokenizers/char_bpe_tokenizer"
+require_relative "tokenizers/chat_template"
+require_relative "tokenizers/copyable"
+require_relative "tokenizers/decode_stream"
 require_relative "tokenizers/encoding"
 require_relative "tokenizers/from_pretrained"
+require_relative "tokenizers/pre_tokenized_string"
+require_relative "tokenizers/regex"
 require_relative "tokenizers/tokenizer"
 require_relative "tokenizers/version"
 
 module Tokenizers
-  class Error < StandardError; end
+  class Error < StandardError
+    # set for errors reading or writing a file
+    attr_reader :path
+
+    def initialize(message = nil, path: nil)
+      super(message)
+      @path = path
+    end
+  end
+
+  class EncodingError < Error; end
+  class TrainingError < Error; end
+
+  class DisallowedSpecialTokenError < Error
+    attr_reader :token, :token_id
+
+    def initialize(message = nil, token: nil, token_id: nil)
+      super(message)
+      @token = token
+      @token_id = token_id
+    end
+  end
+
+  class IOError < Error; end
+
+  class RegexError < Error
+    attr_reader :pattern
+
+    def initialize(message = nil, pattern: nil)
+      super(message)
+      @pattern = pattern
+    end
+  end
+
+  class SerializationError < Error; end
+
+  # subclass of ArgumentError for compatibility
+  class UnknownKeywordError < ArgumentError
+    attr_reader :keywords
+
+    def initialize(message = nil, keywords: [])
+      super(message)
+      @keywords = keywords
+    end
+  end
 
   def self.from_pretrained(...)
     Tokenizer.from_pretrained(...)
@@ -58,4 +110,8 @@ module Tokenizers
   def self.from_file(...)
     Tokenizer.from_file(...)
   end
+
+  def self.from_str(...)
+    Tokenizer.from_str(...)
+  end
 end
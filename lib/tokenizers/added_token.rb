@@ -0,0 +1,11 @@
+module Tokenizers
+  class AddedToken
+    def self.new(content, **kwargs)
+      _new(content, kwargs)
+    end
+
+    def inspect
+      "#<#{self.class.name} #{content.inspect}, special: #{special}, single_word: #{single_word}, lstrip: #{lstrip}, rstrip: #{rstrip}, normalized: #{normalized}>"
+    end
+  end
+end
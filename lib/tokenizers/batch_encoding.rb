@@ -0,0 +1,35 @@
+module Tokenizers
+  class BatchEncoding
+    include Enumerable
+
+    # same as Array, but slices return a BatchEncoding
+    def [](index, length = nil)
+      return _get(index) if length.nil? && !index.is_a?(Range)
+
+      rows = (0...size).to_a
+      rows = length.nil? ? rows[index] : rows[index, length]
+      rows && _select(rows)
+    end
+
+    def each
+      return to_enum(:each) { size } unless block_given?
+
+      size.times do |i|
+        yield _get(i)
+      end
+      self
+    end
+
+    def packed(field = :ids, type: :i64, pad_value: 0, length: nil)
+      _packed(field.to_s, type.to_s, pad_value, length)
+    end
+
+    alias_method :length, :size
+    alias_method :slice, :[]
+    alias_method :type_ids, :token_type_ids
+
+    def inspect
+      "#<#{self.class.name} size=#{size}>"
+    end
+  end
+end
@@ -0,0 +1,43 @@
+module Tokenizers
+  class ChatTemplate
+    def self.new(template, bos_token: nil, eos_token: nil)
+      _new(template, bos_token, eos_token)
+    end
+
+    # config from tokenizer_config.json
+    def self.from_config(config, name: "default")
+      template = config["chat_template"]
+      if template.is_a?(Array)
+        templates = template.to_h { |v| [v["name"], v["template"]] }
+        template = templates.fetch(name) { raise ArgumentError, "Unknown chat template: #{name}" }
+      end
+      raise Error, "No chat template in config" unless template
+
+      new(template, bos_token: special_token(config["bos_token"]), eos_token: special_token(config["eos_token"]))
+    end
+
+    def self.from_file(path, **options)
+      require "json"
+
+      from_config(JSON.parse(File.read(path)), **options)
+    end
+
+    def render(messages, add_generation_prompt: false, **kwargs)
+      _render(messages, add_generation_prompt, kwargs)
+    end
+
+    def _dump(_level)
+      Marshal.dump([source, bos_token, eos_token])
+    end
+
+    def self._load(data)
+      source, bos_token, eos_token = Marshal.load(data)
+      new(source, bos_token: bos_token, eos_token: eos_token)
+    end
+
+    def self.special_token(token)
+      token.is_a?(Hash) ? token["content"] : token
+    end
+    private_class_method :special_token
+  end
+end
@@ -0,0 +1,30 @@
+module Tokenizers
+  # wrapped objects can't be allocated by Ruby, so dup and clone
+  # make a deep copy on the Rust side
+  module Copyable
+    def dup
+      copy = _copy
+      instance_variables.each do |name|
+        copy.instance_variable_set(name, instance_variable_get(name))
+      end
+      copy
+    end
+
+    def clone(freeze: nil)
+      copy = dup
+      copy.freeze if freeze.nil? ? frozen? : freeze
+      copy
+    end
+  end
+
+  [
+    Decoders::Decoder,
+    Models::Model,
+    Normalizers::Normalizer,
+    PreTokenizers::PreTokenizer,
+    Processors::PostProcessor,
+    Trainers::Trainer
+  ].each do |klass|
+    klass.include(Copyable)
+  end
+end
@@ -0,0 +1,17 @@
+module Tokenizers
+  class DecodeStream
+    def self.new(tokenizer, skip_special_tokens: true)
+      stream = _new(skip_special_tokens)
+      stream.instance_variable_set(:@tokenizer, tokenizer)
+      stream
+    end
+
+    def step(id)
+      _step(@tokenizer, id)
+    end
+
+    def flush
+      _flush(@tokenizer)
+    end
+  end
+end
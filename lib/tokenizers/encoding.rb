@@ -15,5 +15,78 @@ module Tokenizers
     def char_to_word(char_pos, sequence_index = 0)
       _char_to_word(word_index, sequence_index)
     end
+
+    def pad(length, direction: "right", pad_id: 0, pad_type_id: 0, pad_token: "[PAD]")
+      _pad(length, direction.to_s, pad_id, pad_type_id, pad_token)
+    end
+
+    def truncate(max_length, stride: 0, direction: "right")
+      _truncate(max_length, stride, direction.to_s)
+    end
+
+    def self.merge(encodings, growing_offsets: true)
+      _merge(encodings.to_a, growing_offsets)
+    end
+
+    def to_h
+      {
+        "ids" => ids,
+        "type_ids" => type_ids,
+        "tokens" => tokens,
+        "word_ids" => word_ids,
+        "offsets" => offsets,
+        "special_tokens_mask" => special_tokens_mask,
+        "attention_mask" => attention_mask,
+        "sequence_ids" => sequence_ids,
+        "overflowing" => overflowing.map(&:to_h)
+      }
+    end
+
+    def self.from_h(hash)
+      hash = hash.transform_keys(&:to_s)
+      _from_parts(
+        hash.fetch("ids"),
+        hash.fetch("type_ids"),
+        hash.fetch("tokens"),
+        hash.fetch("word_ids"),
+        hash.fetch("offsets"),
+        hash.fetch("special_tokens_mask"),
+        hash.fetch("attention_mask"),
+        hash.fetch("sequence_ids"),
+        hash.fetch("overflowing", []).map { |v| v.is_a?(Encoding) ? v : from_h(v) }
+      )
+    end
+
+    def _dump(_level)
+      to_bytes
+    end
+
+    def self._load(data)
+      from_bytes(data)
+    end
+
+    def ids_packed(type = :u32)
+      _packed("ids", type.to_s)
+    end
+
+    def type_ids_packed(type = :u32)
+      _packed("type_ids", type.to_s)
+    end
+
+    def attention_mask_packed(type = :u32)
+      _packed("attention_mask", type.to_s)
+    end
+
+    def special_tokens_mask_packed(type = :u32)
+      _packed("special_tokens_mask", type.to_s)
+    end
+
+    def self.pack_batch(encodings, field = :ids, type: :i64, pad_value: 0, length: nil)
+      if encodings.is_a?(BatchEncoding)
+        return encodings.packed(field, type: type, pad_value: pad_value, length: length)
+      end
+
+      _pack_batch(encodings.to_a, field.to_s, type.to_s, pad_value, length)
+    end
   end
 end
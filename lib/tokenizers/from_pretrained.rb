@@ -36,11 +36,34 @@ module Tokenizers
           raise Error, "Model \"#{identifier}\" on the Hub doesn't have a tokenizer"
         end
 
-      from_file(path)
+      tokenizer = from_file(path)
+
+      # chat template is optional, so errors only warn
+      config_url = "https://huggingface.co/%s/resolve/%s/tokenizer_config.json" % [identifier, revision].map { |v| CGI.escape(v) }
+      config_path =
+        begin
+          cached_path(cache_dir, config_url, headers, options)
+        rescue OpenURI::HTTPError
+          nil
+        rescue SocketError, SystemCallError, Timeout::Error => e
+          warn "[tokenizers] Could not download tokenizer_config.json: #{e.message}"
+          nil
+        end
+      tokenizer.chat_template = chat_template_from_file(config_path) if config_path
+
+      tokenizer
     end
 
     private
 
+    def chat_template_from_file(path)
+      config = JSON.parse(File.read(path))
+      ChatTemplate.from_config(config) if config.is_a?(Hash) && config["chat_template"]
+    rescue JSON::ParserError, ArgumentError, Error => e
+      warn "[tokenizers] Could not load chat template: #{e.message}"
+      nil
+    end
+
     # use same storage format as Rust version
     # https://github.com/epwalsh/rust-cached-path
     def cached_path(cache_dir, url, headers, options)
@@ -0,0 +1,7 @@
+module Tokenizers
+  class PreTokenizedString
+    def get_splits(offset_referential: :original, offset_type: :char)
+      _get_splits(offset_referential.to_s, offset_type.to_s)
+    end
+  end
+end
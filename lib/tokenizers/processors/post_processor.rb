@@ -0,0 +1,9 @@
+module Tokenizers
+  module Processors
+    class PostProcessor
+      def process(encoding, pair = nil, add_special_tokens: true)
+        _process(encoding, pair, add_special_tokens)
+      end
+    end
+  end
+end
@@ -0,0 +1,17 @@
+module Tokenizers
+  class Regex
+    def ==(other)
+      other.is_a?(Regex) && source == other.source
+    end
+
+    alias_method :eql?, :==
+
+    def hash
+      [self.class, source].hash
+    end
+
+    def inspect
+      "#<#{self.class.name} #{source.inspect}>"
+    end
+  end
+end
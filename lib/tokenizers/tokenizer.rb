@@ -1,21 +1,68 @@
 module Tokenizers
   class Tokenizer
     extend FromPretrained
+    include Copyable
 
     def to_s(pretty: false)
       _to_s(pretty)
     end
 
+    # encode_special_tokens and the chat template are not part of the JSON
+    def _dump(_level)
+      Marshal.dump([to_s, encode_special_tokens, chat_template])
+    end
+
+    def self._load(data)
+      json, encode_special_tokens, chat_template = Marshal.load(data)
+      tokenizer = from_str(json)
+      tokenizer.encode_special_tokens = encode_special_tokens
+      tokenizer.chat_template = chat_template
+      tokenizer
+    end
+
+    # components returned or assigned before freezing share state with the tokenizer,
+    # so they're replaced with copies
+    def freeze
+      _detach_components unless frozen?
+      super
+    end
+
+    def self.from_io(io)
+      from_buffer(io.read)
+    end
+
     def save(path, pretty: false)
-      _save(path, pretty)
+      if path.respond_to?(:write)
+        path.write(to_s(pretty: pretty))
+      else
+        _save(path, pretty)
+      end
     end
 
-    def encode(sequence, pair = nil, is_pretokenized: false, add_special_tokens: true)
-      _encode(sequence, pair, is_pretokenized, add_special_tokens)
+    def train_from_iterator(iterator, trainer: nil, length: nil)
+      _train_from_iterator(iterator.each_slice(1000), trainer, length)
     end
 
-    def encode_batch(input, is_pretokenized: false, add_special_tokens: true)
-      _encode_batch(input, is_pretokenized, add_special_tokens)
+    def encode(sequence, pair = nil, is_pretokenized: false, add_special_tokens: true, encode_special_tokens: nil, allowed_special: nil, disallowed_special: nil, offset_type: :char)
+      allowed_special = special_tokens_allowed(encode_special_tokens, allowed_special)
+      _encode(sequence, pair, is_pretokenized, add_special_tokens, allowed_special, disallowed_special, offset_type.to_s)
+    end
+
+    def encode_batch(input, is_pretokenized: false, add_special_tokens: true, encode_special_tokens: nil, allowed_special: nil, disallowed_special: nil, offset_type: :char, return_overflowing_tokens: false)
+      allowed_special = special_tokens_allowed(encode_special_tokens, allowed_special)
+      _encode_batch(input, is_pretokenized, add_special_tokens, allowed_special, disallowed_special, offset_type.to_s, return_overflowing_tokens)
+    end
+
+    def count_tokens(text, add_special_tokens: true)
+      _count_tokens(text, add_special_tokens)
+    end
+
+    def count_tokens_batch(texts, add_special_tokens: true)
+      _count_tokens_batch(texts, add_special_tokens)
+    end
+
+    def chunk(text, max_tokens:, overlap: 0, respect: :word)
+      _chunk(text, max_tokens, overlap, respect.to_s)
     end
 
     def decode(ids, skip_special_tokens: true)
@@ -26,6 +73,25 @@ module Tokenizers
       _decode_batch(sequences, skip_special_tokens)
     end
 
+    attr_reader :chat_template
+
+    def chat_template=(template)
+      template = ChatTemplate.new(template) if template.is_a?(String)
+      @chat_template = template
+    end
+
+    def apply_chat_template(messages, add_generation_prompt: false, tokenize: true, **kwargs)
+      raise Error, "Tokenizer does not have a chat template" unless chat_template
+
+      text = chat_template.render(messages, add_generation_prompt: add_generation_prompt, **kwargs)
+      # special tokens are part of the template
+      tokenize ? encode(text, add_special_tokens: false) : text
+    end
+
+    def decode_stream(skip_special_tokens: true)
+      DecodeStream.new(self, skip_special_tokens: skip_special_tokens)
+    end
+
     def enable_padding(**options)
       _enable_padding(options)
     end
@@ -41,5 +107,17 @@ module Tokenizers
     def vocab_size(with_added_tokens: true)
       _vocab_size(with_added_tokens)
     end
+
+    private
+
+    def special_tokens_allowed(encode_special_tokens, allowed_special)
+      return allowed_special if encode_special_tokens.nil?
+
+      unless allowed_special.nil?
+        raise ArgumentError, "Cannot pass both encode_special_tokens and allowed_special"
+      end
+
+      encode_special_tokens ? [] : :all
+    end
   end
 end
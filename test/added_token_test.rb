@@ -0,0 +1,87 @@
+require_relative "test_helper"
+
+class AddedTokenTest < Minitest::Test
+  def test_defaults
+    token = Tokenizers::AddedToken.new("[NEW]")
+    assert_equal "[NEW]", token.content
+    assert_equal "[NEW]", token.to_s
+    assert_equal false, token.special
+    assert_equal false, token.single_word
+    assert_equal false, token.lstrip
+    assert_equal false, token.rstrip
+    assert_equal true, token.normalized
+  end
+
+  def test_options
+    token = Tokenizers::AddedToken.new("[NEW]", special: true, single_word: true, lstrip: true, rstrip: true, normalized: false)
+    assert_equal true, token.special
+    assert_equal true, token.single_word
+    assert_equal true, token.lstrip
+    assert_equal true, token.rstrip
+    assert_equal false, token.normalized
+  end
+
+  def test_special_not_normalized
+    token = Tokenizers::AddedToken.new("[NEW]", special: true)
+    assert_equal false, token.normalized
+  end
+
+  def test_unknown_keyword
+    error = assert_raises(Tokenizers::UnknownKeywordError) do
+      Tokenizers::AddedToken.new("[NEW]", bad: true)
+    end
+    assert_equal "unknown keyword: :bad", error.message
+    assert_equal [:bad], error.keywords
+    assert_kind_of ArgumentError, error
+
+    error = assert_raises(Tokenizers::UnknownKeywordError) do
+      Tokenizers::AddedToken.new("[NEW]", bad: true, other: false)
+    end
+    assert_equal "unknown keywords: :bad, :other", error.message
+    assert_equal [:bad, :other], error.keywords
+  end
+
+  def test_add_tokens
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    assert_equal 2, tokenizer.add_tokens(["mellifluous", Tokenizers::AddedToken.new("[NEW]", special: true)])
+
+    encoded = tokenizer.encode("mellifluous [NEW]", add_special_tokens: false)
+    assert_equal ["mellifluous", "[NEW]"], encoded.tokens
+    # the mask only marks tokens added by the post-processor
+    assert_equal [0, 0], encoded.special_tokens_mask
+    assert_equal "mellifluous", tokenizer.decode(encoded.ids)
+  end
+
+  def test_add_special_tokens
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    assert_equal 1, tokenizer.add_special_tokens([Tokenizers::AddedToken.new("[NEW]")])
+
+    encoded = tokenizer.encode("hello [NEW]")
+    assert_equal ["[CLS]", "hello", "[NEW]", "[SEP]"], encoded.tokens
+    assert_equal [1, 0, 0, 1], encoded.special_tokens_mask
+    assert_equal "hello", tokenizer.decode(encoded.ids)
+  end
+
+  def test_single_word
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.add_tokens([Tokenizers::AddedToken.new("ing", single_word: true)])
+    refute_includes tokenizer.encode("singing", add_special_tokens: false).tokens, "ing"
+  end
+
+  def test_invalid
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    assert_raises(TypeError) do
+      tokenizer.add_tokens([1])
+    end
+  end
+
+  def test_trainer
+    token = Tokenizers::AddedToken.new("[UNK]", lstrip: true)
+    trainer = Tokenizers::Trainers::BpeTrainer.new(special_tokens: [token, "[CLS]"])
+    assert_equal ["[UNK]", "[CLS]"], trainer.special_tokens
+
+    trainer = Tokenizers::Trainers::WordPieceTrainer.new
+    trainer.special_tokens = [token]
+    assert_equal ["[UNK]"], trainer.special_tokens
+  end
+end
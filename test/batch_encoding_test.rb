@@ -0,0 +1,82 @@
+require_relative "test_helper"
+
+class BatchEncodingTest < Minitest::Test
+  def test_works
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.enable_padding
+    batch = tokenizer.encode_batch(["Hello", "I can feel the magic, can you?"])
+
+    assert_kind_of Tokenizers::BatchEncoding, batch
+    assert_equal 2, batch.size
+    assert_equal 2, batch.length
+    assert_equal 2, batch.count
+    assert_equal batch.map(&:ids), batch.input_ids
+    assert_equal batch.map(&:attention_mask), batch.attention_mask
+    assert_equal batch.map(&:type_ids), batch.token_type_ids
+    assert_equal batch.map(&:tokens), batch.tokens
+    assert_equal batch.map(&:special_tokens_mask), batch.special_tokens_mask
+    assert_equal [0, 1], batch.overflow_to_sample_mapping
+    assert_equal 1, batch.input_ids.map(&:size).uniq.size
+  end
+
+  def test_rows
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    batch = tokenizer.encode_batch(["Hello", "world"])
+
+    assert_kind_of Tokenizers::Encoding, batch[0]
+    assert_equal batch[1].ids, batch[-1].ids
+    assert_nil batch[2]
+    assert_nil batch[-3]
+    assert_equal 2, batch.to_a.size
+    assert_kind_of Enumerator, batch.each
+    assert_equal 2, batch.each.size
+  end
+
+  def test_slices
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    batch = tokenizer.encode_batch(["Hello", "world", "I can feel the magic, can you?"])
+
+    slice = batch[1..]
+    assert_kind_of Tokenizers::BatchEncoding, slice
+    assert_equal batch.input_ids[1..], slice.input_ids
+    assert_equal [1, 2], slice.overflow_to_sample_mapping
+    assert_equal batch.input_ids[0, 2], batch[0, 2].input_ids
+    assert_equal batch.input_ids[-2..-1], batch[-2..-1].input_ids
+    assert_equal batch.input_ids[1...1], batch[1...1].input_ids
+    assert_equal batch.input_ids[3, 1], batch[3, 1].input_ids
+    assert_equal batch.input_ids[1, 10], batch.slice(1, 10).input_ids
+    assert_nil batch[4..]
+    assert_nil batch[4, 1]
+    assert_nil batch[0, -1]
+    assert_equal 2, batch.first(2).size
+  end
+
+  def test_overflowing
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.enable_truncation(4)
+    texts = ["I can feel the magic, can you?", "Hello"]
+
+    batch = tokenizer.encode_batch(texts)
+    assert_equal 2, batch.size
+    assert_equal [0, 1], batch.overflow_to_sample_mapping
+    refute_empty batch[0].overflowing
+
+    batch = tokenizer.encode_batch(texts, return_overflowing_tokens: true)
+    assert_operator batch.size, :>, 2
+    mapping = batch.overflow_to_sample_mapping
+    assert_equal batch.size, mapping.size
+    assert_equal [0] * (batch.size - 1) + [1], mapping
+    assert batch.all? { |e| e.overflowing.empty? }
+  end
+
+  def test_packed
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    batch = tokenizer.encode_batch(["Hello", "I can feel the magic, can you?"])
+    cols = batch.input_ids.map(&:size).max
+
+    packed = batch.packed
+    assert_equal batch.size * cols * 8, packed.bytesize
+    assert_equal packed, Tokenizers::Encoding.pack_batch(batch)
+    assert_equal packed, Tokenizers::Encoding.pack_batch(batch.to_a)
+  end
+end
@@ -0,0 +1,106 @@
+require_relative "test_helper"
+
+class ChatTemplateTest < Minitest::Test
+  TEMPLATE = "{{ bos_token }}{% for message in messages %}<|{{ message['role'] }}|>\n{{ message['content'] | trim }}{{ eos_token }}\n{% endfor %}{% if add_generation_prompt %}<|assistant|>\n{% endif %}"
+
+  def messages
+    [
+      {role: "system", content: "You are a helpful assistant."},
+      {"role" => "user", "content" => " Hello! "}
+    ]
+  end
+
+  def test_render
+    template = Tokenizers::ChatTemplate.new(TEMPLATE, bos_token: "<s>", eos_token: "</s>")
+    assert_equal TEMPLATE, template.source
+    assert_equal "<s>", template.bos_token
+    assert_equal "</s>", template.eos_token
+
+    expected = "<s><|system|>\nYou are a helpful assistant.</s>\n<|user|>\nHello!</s>\n"
+    assert_equal expected, template.render(messages)
+    assert_equal "#{expected}<|assistant|>\n", template.render(messages, add_generation_prompt: true)
+  end
+
+  def test_python_methods
+    template = Tokenizers::ChatTemplate.new("{% for message in messages %}{{ message.content.strip().upper() }}{% if message.role.startswith('sys') %}!{% endif %}{% endfor %}")
+    assert_equal "YOU ARE A HELPFUL ASSISTANT.!HELLO!", template.render(messages)
+  end
+
+  def test_variables
+    template = Tokenizers::ChatTemplate.new("{{ tools | tojson }} {{ count + 1 }} {{ flag }} {{ missing is none }}")
+    assert_equal "[{\"name\":\"search\",\"strict\":true}] 2 False True", template.render([], tools: [{name: "search", strict: true}], count: 1, flag: false, missing: nil)
+  end
+
+  def test_raise_exception
+    template = Tokenizers::ChatTemplate.new("{% if messages[0]['role'] != 'user' %}{{ raise_exception('Conversation must start with user') }}{% endif %}")
+    error = assert_raises(Tokenizers::Error) do
+      template.render(messages)
+    end
+    assert_equal "Conversation must start with user", error.message
+  end
+
+  def test_syntax_error
+    assert_raises(Tokenizers::Error) do
+      Tokenizers::ChatTemplate.new("{% for message in messages %}")
+    end
+  end
+
+  def test_invalid_value
+    template = Tokenizers::ChatTemplate.new("{{ messages }}")
+    assert_raises(TypeError) do
+      template.render([Object.new])
+    end
+  end
+
+  def test_from_config
+    config = {
+      "chat_template" => TEMPLATE,
+      "bos_token" => {"content" => "<s>", "special" => true},
+      "eos_token" => "</s>"
+    }
+    template = Tokenizers::ChatTemplate.from_config(config)
+    assert_equal "<s>", template.bos_token
+    assert_equal "</s>", template.eos_token
+
+    config["chat_template"] = [{"name" => "default", "template" => TEMPLATE}, {"name" => "tool_use", "template" => "tools"}]
+    assert_equal TEMPLATE, Tokenizers::ChatTemplate.from_config(config).source
+    assert_equal "tools", Tokenizers::ChatTemplate.from_config(config, name: "tool_use").source
+  end
+
+  def test_from_pretrained_invalid
+    path = "/tmp/tokenizer_config.json"
+    File.write(path, JSON.generate({"chat_template" => "{% for message in messages %}"}))
+    template = nil
+    _, stderr = capture_io do
+      template = Tokenizers::Tokenizer.send(:chat_template_from_file, path)
+    end
+    assert_nil template
+    assert_match "Could not load chat template", stderr
+
+    File.write(path, "{")
+    _, stderr = capture_io do
+      template = Tokenizers::Tokenizer.send(:chat_template_from_file, path)
+    end
+    assert_nil template
+    assert_match "Could not load chat template", stderr
+  end
+
+  def test_apply_chat_template
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    assert_raises(Tokenizers::Error) do
+      tokenizer.apply_chat_template(messages)
+    end
+
+    tokenizer.chat_template = "[CLS] {% for message in messages %}{{ message['content'] }} [SEP] {% endfor %}"
+    assert_instance_of Tokenizers::ChatTemplate, tokenizer.chat_template
+
+    text = tokenizer.apply_chat_template(messages, tokenize: false)
+    assert_equal "[CLS] You are a helpful assistant. [SEP]  Hello!  [SEP] ", text
+
+    encoding = tokenizer.apply_chat_template(messages)
+    assert_instance_of Tokenizers::Encoding, encoding
+    assert_equal "[CLS]", encoding.tokens.first
+    assert_equal "[SEP]", encoding.tokens.last
+    assert_equal 1, encoding.tokens.count("[CLS]")
+  end
+end
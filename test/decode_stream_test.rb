@@ -0,0 +1,52 @@
+require_relative "test_helper"
+
+class DecodeStreamTest < Minitest::Test
+  def test_byte_level
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    text = "Hello 😁 world"
+    ids = tokenizer.encode(text).ids
+
+    stream = tokenizer.decode_stream
+    chunks = ids.map { |id| stream.step(id) }
+    assert_includes chunks, nil
+    refute chunks.compact.any? { |chunk| chunk.include?("�") }
+    assert_equal text, chunks.compact.join
+  end
+
+  def test_word_piece
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    text = "I can feel the magic, can you?"
+    ids = tokenizer.encode(text).ids
+
+    stream = Tokenizers::DecodeStream.new(tokenizer)
+    assert_equal true, stream.skip_special_tokens
+    chunks = ids.map { |id| stream.step(id) }
+    assert_equal tokenizer.decode(ids), chunks.compact.join
+  end
+
+  def test_skip_special_tokens
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    ids = tokenizer.encode("Hello").ids
+
+    stream = tokenizer.decode_stream(skip_special_tokens: false)
+    assert_equal false, stream.skip_special_tokens
+    chunks = ids.map { |id| stream.step(id) }
+    assert_equal "[CLS]", chunks.first
+    assert_equal tokenizer.decode(ids, skip_special_tokens: false), chunks.compact.join
+  end
+
+  def test_flush
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    ids = tokenizer.encode("Hello 😁").ids
+
+    stream = tokenizer.decode_stream
+    chunks = ids[0..-2].map { |id| stream.step(id) }
+    assert_nil chunks.last
+    assert_equal tokenizer.decode(ids[0..-2]), chunks.compact.join + stream.flush
+    assert_nil stream.flush
+
+    chunks = ids.map { |id| stream.step(id) }
+    assert_equal "Hello 😁", chunks.compact.join
+    assert_nil stream.flush
+  end
+end
@@ -120,4 +120,49 @@ class DecoderTest < Minitest::Test
     decoder.cleanup = true
     assert_equal true, decoder.cleanup
   end
+
+  def test_decode
+    decoder = Tokenizers::Decoders::WordPiece.new
+    assert_equal "Hello world", decoder.decode(["Hello", "wo", "##rld"])
+    assert_equal ["Hello", " wo", "rld"], decoder.decode_chain(["Hello", "wo", "##rld"])
+
+    decoder = Tokenizers::Decoders::ByteLevel.new
+    assert_equal "Hello world", decoder.decode(["Hello", "Ġworld"])
+  end
+
+  def test_sequence
+    decoder = Tokenizers::Decoders::Sequence.new([
+      Tokenizers::Decoders::Replace.new("▁", " "),
+      Tokenizers::Decoders::ByteFallback.new,
+      Tokenizers::Decoders::Fuse.new,
+      Tokenizers::Decoders::Strip.new(content: " ", start: 1)
+    ])
+    assert_instance_of Tokenizers::Decoders::Sequence, decoder
+    assert_kind_of Tokenizers::Decoders::Decoder, decoder
+
+    decoders = decoder.decoders
+    assert_equal [Tokenizers::Decoders::Replace, Tokenizers::Decoders::ByteFallback, Tokenizers::Decoders::Fuse, Tokenizers::Decoders::Strip], decoders.map(&:class)
+    assert_equal 1, decoders.last.start
+
+    tokenizer = Tokenizers::Tokenizer.new(Tokenizers::Models::WordLevel.new(vocab: {"▁Hello" => 0, "▁world" => 1}))
+    tokenizer.decoder = decoder
+    tokenizer = Tokenizers::Tokenizer.from_str(tokenizer.to_s)
+    assert_instance_of Tokenizers::Decoders::Sequence, tokenizer.decoder
+    assert_equal "Hello world", tokenizer.decode([0, 1])
+  end
+
+  def test_dup
+    decoder = Tokenizers::Decoders::BPEDecoder.new(suffix: "</end>")
+    copy = decoder.dup
+    copy.suffix = "</w>"
+    assert_equal "</end>", decoder.suffix
+    assert_equal "</w>", copy.suffix
+  end
+
+  def test_marshal
+    decoder = Tokenizers::Decoders::Metaspace.new(replacement: "_")
+    copy = Marshal.load(Marshal.dump(decoder))
+    assert_instance_of Tokenizers::Decoders::Metaspace, copy
+    assert_equal "_", copy.replacement
+  end
 end
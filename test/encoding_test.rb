@@ -0,0 +1,205 @@
+require_relative "test_helper"
+
+class EncodingTest < Minitest::Test
+  def test_ids_packed
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("I can feel the magic, can you?")
+
+    packed = encoding.ids_packed
+    assert_equal Encoding::BINARY, packed.encoding
+    assert_equal encoding.ids, packed.unpack("L*")
+    assert_equal encoding.ids, encoding.ids_packed(:u16).unpack("S*")
+    assert_equal encoding.ids, encoding.ids_packed(:i32).unpack("l*")
+    assert_equal encoding.ids, encoding.ids_packed(:i64).unpack("q*")
+    assert_equal encoding.type_ids, encoding.type_ids_packed.unpack("L*")
+    assert_equal encoding.attention_mask, encoding.attention_mask_packed(:i64).unpack("q*")
+    assert_equal encoding.special_tokens_mask, encoding.special_tokens_mask_packed(:u16).unpack("S*")
+  end
+
+  def test_ids_packed_long
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("I can feel the magic, can you? " * 1000)
+
+    packed = encoding.ids_packed(:i64)
+    assert_equal Encoding::BINARY, packed.encoding
+    assert_equal encoding.ids.size * 8, packed.bytesize
+    assert_equal encoding.ids, packed.unpack("q*")
+  end
+
+  def test_ids_packed_out_of_range
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    encoding = tokenizer.encode("<|endoftext|>")
+    assert_raises(RangeError) do
+      Tokenizers::Encoding.pack_batch([encoding], type: :u16, pad_value: 70000, length: 2)
+    end
+    assert_raises(ArgumentError) do
+      encoding.ids_packed(:f32)
+    end
+  end
+
+  def test_pack_batch
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encodings = tokenizer.encode_batch(["Hello", "I can feel the magic, can you?"])
+    cols = encodings.map { |e| e.ids.size }.max
+
+    packed = Tokenizers::Encoding.pack_batch(encodings)
+    assert_equal encodings.size * cols * 8, packed.bytesize
+    rows = packed.unpack("q*").each_slice(cols).to_a
+    assert_equal encodings[0].ids + [0] * (cols - encodings[0].ids.size), rows[0]
+    assert_equal encodings[1].ids, rows[1]
+
+    packed = Tokenizers::Encoding.pack_batch(encodings, :attention_mask, type: :u32, length: cols + 2)
+    rows = packed.unpack("L*").each_slice(cols + 2).to_a
+    assert_equal encodings[0].attention_mask + [0] * (cols + 2 - encodings[0].ids.size), rows[0]
+    assert_equal encodings[1].attention_mask + [0, 0], rows[1]
+
+    assert_raises(ArgumentError) do
+      Tokenizers::Encoding.pack_batch(encodings, length: 1)
+    end
+    assert_raises(ArgumentError) do
+      Tokenizers::Encoding.pack_batch(encodings, :tokens)
+    end
+  end
+
+  def test_pad
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("Hello")
+    size = encoding.ids.size
+
+    padded = encoding.pad(size + 2, pad_id: 5)
+    assert_equal encoding.ids + [5, 5], padded.ids
+    assert_equal encoding.tokens + ["[PAD]", "[PAD]"], padded.tokens
+    assert_equal [1] * size + [0, 0], padded.attention_mask
+    assert_equal size, encoding.ids.size
+
+    padded = encoding.pad(size + 1, direction: :left, pad_token: "<pad>")
+    assert_equal ["<pad>"] + encoding.tokens, padded.tokens
+
+    assert_raises(ArgumentError) do
+      encoding.pad(size + 1, direction: :up)
+    end
+  end
+
+  def test_truncate
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("I can feel the magic, can you?")
+
+    truncated = encoding.truncate(4)
+    assert_equal encoding.ids.first(4), truncated.ids
+    refute_empty truncated.overflowing
+
+    truncated = encoding.truncate(4, direction: :left)
+    assert_equal encoding.ids.last(4), truncated.ids
+
+    truncated = encoding.truncate(4, stride: 2)
+    assert_equal encoding.ids[2, 2], truncated.overflowing[0].ids.first(2)
+
+    assert_raises(ArgumentError) do
+      encoding.truncate(4, stride: 4)
+    end
+  end
+
+  def test_merge
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    first = tokenizer.encode("Hello", add_special_tokens: false)
+    second = tokenizer.encode("world", add_special_tokens: false)
+
+    merged = Tokenizers::Encoding.merge([first, second])
+    assert_equal first.ids + second.ids, merged.ids
+    assert_equal [[0, 5], [5, 10]], merged.offsets
+
+    merged = Tokenizers::Encoding.merge([first, second], growing_offsets: false)
+    assert_equal [[0, 5], [0, 5]], merged.offsets
+  end
+
+  def test_slice
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("I can feel the magic, can you?")
+
+    sliced = encoding.slice(1..3)
+    assert_equal encoding.ids[1..3], sliced.ids
+    assert_equal encoding.offsets[1..3], sliced.offsets
+    assert_equal encoding.tokens[-2..], encoding.slice(-2..).tokens
+    assert_equal [], encoding.slice(0...0).ids
+
+    assert_raises(RangeError) do
+      encoding.slice(100..)
+    end
+  end
+
+  def test_sequence
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("Hello, y'all!", "How are you?")
+    assert_equal 2, encoding.n_sequences
+
+    first = encoding.sequence(0)
+    assert_equal ["Hello", ",", "y", "'", "all", "!"], first.tokens
+    assert_equal [0] * first.ids.size, first.type_ids
+
+    second = encoding.sequence(1)
+    assert_equal ["How", "are", "you", "?"], second.tokens
+    assert_equal [0, 3], second.offsets[0]
+    assert_equal [0] * second.ids.size, second.sequence_ids
+
+    assert_nil encoding.sequence(2)
+  end
+
+  def test_to_h
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.enable_truncation(4)
+    encoding = tokenizer.encode("Hello, y'all!", "How are you?")
+
+    hash = encoding.to_h
+    assert_equal encoding.ids, hash["ids"]
+    assert_equal encoding.sequence_ids, hash["sequence_ids"]
+    assert_equal encoding.overflowing.size, hash["overflowing"].size
+
+    restored = Tokenizers::Encoding.from_h(hash)
+    assert_encoding_equal encoding, restored
+    assert_equal hash, restored.to_h
+
+    restored = Tokenizers::Encoding.from_h(JSON.parse(JSON.generate(hash)))
+    assert_equal hash, restored.to_h
+
+    restored = Tokenizers::Encoding.from_h(hash.transform_keys(&:to_sym))
+    assert_equal encoding.ids, restored.ids
+
+    assert_raises(ArgumentError) do
+      Tokenizers::Encoding.from_h(hash.merge("ids" => [1]))
+    end
+    assert_raises(KeyError) do
+      Tokenizers::Encoding.from_h({"ids" => []})
+    end
+  end
+
+  def test_marshal
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("Hello, y'all!", "How are you?")
+
+    restored = Marshal.load(Marshal.dump(encoding))
+    assert_encoding_equal encoding, restored
+    assert_equal encoding.to_h, restored.to_h
+
+    bytes = encoding.to_bytes
+    assert_equal Encoding::BINARY, bytes.encoding
+    assert_equal encoding.to_h, Tokenizers::Encoding.from_bytes(bytes).to_h
+
+    assert_raises(Tokenizers::SerializationError) do
+      Tokenizers::Encoding.from_bytes("")
+    end
+    assert_raises(Tokenizers::SerializationError) do
+      Tokenizers::Encoding.from_bytes(bytes[0, 10])
+    end
+  end
+
+  private
+
+  def assert_encoding_equal(expected, actual)
+    assert_equal expected.ids, actual.ids
+    assert_equal expected.tokens, actual.tokens
+    assert_equal expected.offsets, actual.offsets
+    assert_equal expected.n_sequences, actual.n_sequences
+    assert_equal expected.token_to_sequence(1), actual.token_to_sequence(1)
+    assert_equal expected.word_to_chars(0, 1), actual.word_to_chars(0, 1)
+  end
+end
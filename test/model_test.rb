@@ -57,6 +57,38 @@ class ModelTest < Minitest::Test
     assert_equal "[PAD]", model.unk_token
   end
 
+  def test_word_level_bad_vocab
+    path = "/tmp/word-level-vocab.json"
+    File.write(path, JSON.generate(["am"]))
+
+    error = assert_raises(Tokenizers::Error) do
+      Tokenizers::Models::WordLevel.read_file(path)
+    end
+    assert_equal "Bad vocabulary json file", error.message
+    assert_equal path, error.path
+
+    error = assert_raises(Tokenizers::Error) do
+      Tokenizers::Models::WordLevel.from_file(path)
+    end
+    assert_equal path, error.path
+  end
+
+  def test_bpe_bad_files
+    vocab = "test/support/roberta-base-vocab.json"
+    merges = "/tmp/bpe-merges.txt"
+    File.write(merges, "a\n")
+
+    error = assert_raises(Tokenizers::Error) do
+      Tokenizers::CharBPETokenizer.new(vocab, merges)
+    end
+    assert_equal merges, error.path
+
+    error = assert_raises(Tokenizers::IOError) do
+      Tokenizers::CharBPETokenizer.new("missing.json", merges)
+    end
+    assert_equal "missing.json", error.path
+  end
+
   def test_word_piece
     model = Tokenizers::Models::WordPiece.new
     assert_instance_of Tokenizers::Models::WordPiece, model
@@ -90,4 +122,20 @@ class ModelTest < Minitest::Test
 
     Tokenizers::Models::Unigram.new(vocab: [["a", 0.117], ["b", 0.786]])
   end
+
+  def test_dup
+    model = Tokenizers::Models::BPE.new(unk_token: "[UNK]")
+    copy = model.dup
+    copy.unk_token = "[PAD]"
+    assert_instance_of Tokenizers::Models::BPE, copy
+    assert_equal "[UNK]", model.unk_token
+    assert_equal "[PAD]", copy.unk_token
+  end
+
+  def test_marshal
+    model = Tokenizers::Models::WordPiece.new(vocab: {"[UNK]" => 0, "a" => 1}, unk_token: "[UNK]")
+    copy = Marshal.load(Marshal.dump(model))
+    assert_instance_of Tokenizers::Models::WordPiece, copy
+    assert_equal "[UNK]", copy.unk_token
+  end
 end
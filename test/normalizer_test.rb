@@ -75,6 +75,21 @@ class NormalizerTest < Minitest::Test
     normalizer = Tokenizers::Normalizers::Replace.new('abc', 'xyz')
     assert_instance_of Tokenizers::Normalizers::Replace, normalizer
     assert_kind_of Tokenizers::Normalizers::Replace, normalizer
+
+    assert_equal "abc", normalizer.pattern
+    assert_equal "xyz", normalizer.content
+  end
+
+  def test_replace_regex
+    regex = Tokenizers::Regex.new("\\s+")
+    normalizer = Tokenizers::Normalizers::Replace.new(regex, " ")
+    assert_equal "a b c", normalizer.normalize_str("a  b\tc")
+    assert_equal regex, normalizer.pattern
+    assert_equal " ", normalizer.content
+
+    copy = Marshal.load(Marshal.dump(normalizer))
+    assert_equal regex, copy.pattern
+    assert_equal "a b c", copy.normalize_str("a  b\tc")
   end
 
   def test_prepend
@@ -108,4 +123,105 @@ class NormalizerTest < Minitest::Test
     assert_instance_of Tokenizers::Normalizers::StripAccents, normalizer
     assert_kind_of Tokenizers::Normalizers::StripAccents, normalizer
   end
+
+  def test_precompiled
+    tokenizer = Tokenizers.from_pretrained("t5-small")
+    assert_instance_of Tokenizers::Normalizers::Precompiled, tokenizer.normalizer
+    config = JSON.parse(tokenizer.to_s)["normalizer"]
+    assert_equal ["type", "precompiled_charsmap"], config.keys
+
+    normalizer = Tokenizers::Normalizers::Precompiled.new(config["precompiled_charsmap"].unpack1("m"))
+    assert_instance_of Tokenizers::Normalizers::Precompiled, normalizer
+    assert_kind_of Tokenizers::Normalizers::Normalizer, normalizer
+    assert_equal "ABC 123", normalizer.normalize_str("ＡＢＣ １２３")
+
+    charsmap = tokenizer.normalizer.precompiled_charsmap
+    assert_equal Encoding::BINARY, charsmap.encoding
+    assert_equal config["precompiled_charsmap"].unpack1("m"), charsmap
+    assert_equal charsmap, normalizer.precompiled_charsmap
+
+    assert_raises(Tokenizers::Error) do
+      Tokenizers::Normalizers::Precompiled.new("")
+    end
+  end
+
+  class UpcaseNormalizer
+    def normalize(normalized)
+      normalized.uppercase
+      normalized.replace("-", " ")
+      normalized.filter { |c| c != "!" }
+    end
+  end
+
+  class LeakyNormalizer
+    attr_reader :normalized
+
+    def normalize(normalized)
+      @normalized = normalized
+    end
+  end
+
+  class ErrorNormalizer
+    def normalize(normalized)
+      raise ArgumentError, "bad input"
+    end
+  end
+
+  def test_custom
+    normalizer = Tokenizers::Normalizers::Custom.new(UpcaseNormalizer.new)
+    assert_instance_of Tokenizers::Normalizers::Custom, normalizer
+    assert_kind_of Tokenizers::Normalizers::Normalizer, normalizer
+    assert_equal "HELLO WORLD", normalizer.normalize_str("hello-world!")
+  end
+
+  def test_custom_sequence
+    normalizer = Tokenizers::Normalizers::Sequence.new([
+      Tokenizers::Normalizers::NFD.new,
+      Tokenizers::Normalizers::StripAccents.new,
+      Tokenizers::Normalizers::Custom.new(UpcaseNormalizer.new)
+    ])
+    assert_equal "HELLO HOW ARE U?", normalizer.normalize_str("Héllò hôw are ü?!")
+  end
+
+  def test_custom_normalized_string
+    inner = LeakyNormalizer.new
+    normalizer = Tokenizers::Normalizers::Custom.new(inner)
+    normalizer.normalize_str("hello")
+    assert_instance_of Tokenizers::NormalizedString, inner.normalized
+    error = assert_raises(Tokenizers::Error) do
+      inner.normalized.normalized
+    end
+    assert_equal "Cannot use this object outside of the method it was passed to", error.message
+  end
+
+  def test_custom_error
+    normalizer = Tokenizers::Normalizers::Custom.new(ErrorNormalizer.new)
+    error = assert_raises(ArgumentError) do
+      normalizer.normalize_str("hello")
+    end
+    assert_equal "bad input", error.message
+  end
+
+  def test_custom_serialization
+    normalizer = Tokenizers::Normalizers::Custom.new(UpcaseNormalizer.new)
+    error = assert_raises(Tokenizers::SerializationError) do
+      Marshal.dump(normalizer)
+    end
+    assert_equal "Custom Normalizer cannot be serialized", error.message
+  end
+
+  def test_dup
+    normalizer = Tokenizers::Normalizers::BertNormalizer.new(lowercase: false)
+    copy = normalizer.dup
+    copy.lowercase = true
+    assert_equal false, normalizer.lowercase
+    assert_equal true, copy.lowercase
+  end
+
+  def test_marshal
+    normalizer = Tokenizers::Normalizers::Sequence.new([Tokenizers::Normalizers::NFD.new, Tokenizers::Normalizers::StripAccents.new])
+    copy = Marshal.load(Marshal.dump(normalizer))
+    assert_instance_of Tokenizers::Normalizers::Sequence, copy
+    assert_equal "Hello how are u?", copy.normalize_str("Héllò hôw are ü?")
+  end
 end
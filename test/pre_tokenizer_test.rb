@@ -96,6 +96,47 @@ class PreTokenizerTest < Minitest::Test
     assert_raises(ArgumentError) { Tokenizers::PreTokenizers::Split.new("abc", "invalid") }
   end
 
+  def test_split_regex
+    regex = Tokenizers::Regex.new("\\d+")
+    assert_equal "\\d+", regex.source
+    assert regex.eql?(Tokenizers::Regex.new("\\d+"))
+    assert_equal regex.hash, Tokenizers::Regex.new("\\d+").hash
+    assert_equal 1, [regex, Tokenizers::Regex.new("\\d+")].uniq.size
+
+    pre_tokenizer = Tokenizers::PreTokenizers::Split.new(regex, "isolated")
+    assert_equal [["ab", [0, 2]], ["12", [2, 4]], ["cd", [4, 6]]], pre_tokenizer.pre_tokenize_str("ab12cd")
+    assert_equal regex, pre_tokenizer.pattern
+    assert_equal "isolated", pre_tokenizer.behavior
+    assert_equal false, pre_tokenizer.invert
+
+    pre_tokenizer = Tokenizers::PreTokenizers::Split.new("-", "merged_with_next", invert: true)
+    assert_equal "-", pre_tokenizer.pattern
+    assert_equal "merged_with_next", pre_tokenizer.behavior
+    assert_equal true, pre_tokenizer.invert
+  end
+
+  def test_split_regex_serialization
+    # GPT-2 pattern
+    pattern = "'s|'t|'re|'ve|'m|'ll|'d| ?\\p{L}+| ?\\p{N}+| ?[^\\s\\p{L}\\p{N}]+|\\s+(?!\\S)|\\s+"
+    tokenizer = Tokenizers::Tokenizer.new(Tokenizers::Models::WordLevel.new(vocab: {"[UNK]" => 0}, unk_token: "[UNK]"))
+    tokenizer.pre_tokenizer = Tokenizers::PreTokenizers::Split.new(Tokenizers::Regex.new(pattern), "isolated")
+
+    json = JSON.parse(tokenizer.to_s)
+    assert_equal({"Regex" => pattern}, json["pre_tokenizer"]["pattern"])
+
+    pre_tokenizer = Tokenizers::Tokenizer.from_str(tokenizer.to_s).pre_tokenizer
+    assert_equal Tokenizers::Regex.new(pattern), pre_tokenizer.pattern
+    expected = [["Hello", [0, 5]], [",", [5, 6]], [" world", [6, 12]], ["'s", [12, 14]]]
+    assert_equal expected, pre_tokenizer.pre_tokenize_str("Hello, world's")
+  end
+
+  def test_regex_invalid
+    error = assert_raises(Tokenizers::RegexError) do
+      Tokenizers::Regex.new("(abc")
+    end
+    assert_equal "(abc", error.pattern
+  end
+
   def test_whitespace
     pre_tokenizer = Tokenizers::PreTokenizers::Whitespace.new
     assert_instance_of Tokenizers::PreTokenizers::Whitespace, pre_tokenizer
@@ -107,4 +148,85 @@ class PreTokenizerTest < Minitest::Test
     assert_instance_of Tokenizers::PreTokenizers::WhitespaceSplit, pre_tokenizer
     assert_kind_of Tokenizers::PreTokenizers::PreTokenizer, pre_tokenizer
   end
+
+  class AtomPreTokenizer
+    def pre_tokenize(pretok)
+      pretok.split do |_i, normalized|
+        pos = 0
+        normalized.normalized.scan(/Br|Cl|./).map do |atom|
+          split = normalized.slice(pos...(pos + atom.length))
+          pos += atom.length
+          split
+        end
+      end
+    end
+  end
+
+  class DashPreTokenizer
+    attr_reader :splits
+
+    def pre_tokenize(pretok)
+      pretok.split { |_i, normalized| normalized.split("-", "removed") }
+      @splits = pretok.get_splits(offset_type: :byte)
+    end
+  end
+
+  def test_custom
+    pre_tokenizer = Tokenizers::PreTokenizers::Custom.new(AtomPreTokenizer.new)
+    assert_instance_of Tokenizers::PreTokenizers::Custom, pre_tokenizer
+    assert_kind_of Tokenizers::PreTokenizers::PreTokenizer, pre_tokenizer
+
+    expected = [["C", [0, 1]], ["C", [1, 2]], ["Br", [2, 4]], ["O", [4, 5]]]
+    assert_equal expected, pre_tokenizer.pre_tokenize_str("CCBrO")
+  end
+
+  def test_custom_sequence
+    pre_tokenizer = Tokenizers::PreTokenizers::Sequence.new([
+      Tokenizers::PreTokenizers::WhitespaceSplit.new,
+      Tokenizers::PreTokenizers::Custom.new(AtomPreTokenizer.new)
+    ])
+    expected = [["C", [0, 1]], ["Cl", [1, 3]], ["é", [4, 5]], ["O", [5, 6]]]
+    assert_equal expected, pre_tokenizer.pre_tokenize_str("CCl éO")
+  end
+
+  def test_custom_get_splits
+    inner = DashPreTokenizer.new
+    pre_tokenizer = Tokenizers::PreTokenizers::Custom.new(inner)
+    expected = [["é", [0, 1]], ["b", [2, 3]]]
+    assert_equal expected, pre_tokenizer.pre_tokenize_str("é-b")
+    assert_equal [["é", [0, 2]], ["b", [3, 4]]], inner.splits
+  end
+
+  def test_custom_error
+    pre_tokenizer = Object.new
+    def pre_tokenizer.pre_tokenize(pretok)
+      pretok.split { |_i, normalized| [normalized.normalized] }
+    end
+    pre_tokenizer = Tokenizers::PreTokenizers::Custom.new(pre_tokenizer)
+    assert_raises(TypeError) do
+      pre_tokenizer.pre_tokenize_str("hello")
+    end
+  end
+
+  def test_custom_serialization
+    pre_tokenizer = Tokenizers::PreTokenizers::Custom.new(AtomPreTokenizer.new)
+    error = assert_raises(Tokenizers::SerializationError) do
+      Marshal.dump(pre_tokenizer)
+    end
+    assert_equal "Custom PreTokenizer cannot be serialized", error.message
+  end
+
+  def test_dup
+    pre_tokenizer = Tokenizers::PreTokenizers::Digits.new(individual_digits: false)
+    copy = pre_tokenizer.clone
+    copy.individual_digits = true
+    assert_equal false, pre_tokenizer.individual_digits
+    assert_equal true, copy.individual_digits
+  end
+
+  def test_marshal
+    pre_tokenizer = Tokenizers::PreTokenizers::Sequence.new([Tokenizers::PreTokenizers::Whitespace.new, Tokenizers::PreTokenizers::Digits.new(individual_digits: true)])
+    copy = Marshal.load(Marshal.dump(pre_tokenizer))
+    assert_equal pre_tokenizer.pre_tokenize_str("Call 911!"), copy.pre_tokenize_str("Call 911!")
+  end
 end
@@ -26,6 +26,68 @@ class ProcessorlTest < Minitest::Test
                                                   add_prefix_space: false)
   end
 
+  def test_process
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("Hello", add_special_tokens: false)
+    pair = tokenizer.encode("world", add_special_tokens: false)
+
+    processor = Tokenizers::Processors::TemplateProcessing.new(
+      single: "[CLS] $A [SEP]",
+      pair: "[CLS] $A [SEP] $B:1 [SEP]:1",
+      special_tokens: [["[CLS]", 101], ["[SEP]", 102]]
+    )
+    assert_equal 2, processor.added_tokens(false)
+    assert_equal 3, processor.added_tokens(true)
+
+    processed = processor.process(encoding)
+    assert_equal ["[CLS]", "Hello", "[SEP]"], processed.tokens
+    assert_equal [101, encoding.ids[0], 102], processed.ids
+
+    processed = processor.process(encoding, pair)
+    assert_equal ["[CLS]", "Hello", "[SEP]", "world", "[SEP]"], processed.tokens
+    assert_equal [0, 0, 0, 1, 1], processed.type_ids
+
+    processed = processor.process(encoding, add_special_tokens: false)
+    assert_equal ["Hello"], processed.tokens
+  end
+
+  def test_sequence
+    processor = Tokenizers::Processors::Sequence.new([
+      Tokenizers::Processors::ByteLevel.new(trim_offsets: false),
+      Tokenizers::Processors::TemplateProcessing.new(single: "$A </s>", special_tokens: [["</s>", 1]])
+    ])
+    assert_instance_of Tokenizers::Processors::Sequence, processor
+    assert_kind_of Tokenizers::Processors::PostProcessor, processor
+    assert_equal 1, processor.added_tokens(false)
+
+    processors = processor.processors
+    assert_equal [Tokenizers::Processors::ByteLevel, Tokenizers::Processors::TemplateProcessing], processors.map(&:class)
+    assert_equal 1, processors.last.added_tokens(false)
+
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoding = tokenizer.encode("Hello", add_special_tokens: false)
+    processed = processor.process(encoding)
+    assert_equal ["Hello", "</s>"], processed.tokens
+    assert_equal [encoding.ids[0], 1], processed.ids
+
+    tokenizer.post_processor = processor
+    json = JSON.parse(tokenizer.to_s)["post_processor"]
+    assert_equal "Sequence", json["type"]
+    assert_equal ["ByteLevel", "TemplateProcessing"], json["processors"].map { |p| p["type"] }
+
+    tokenizer = Tokenizers::Tokenizer.from_str(tokenizer.to_s)
+    assert_instance_of Tokenizers::Processors::Sequence, tokenizer.post_processor
+    assert_equal ["Hello", "</s>"], tokenizer.post_processor.process(encoding).tokens
+  end
+
+  def test_pretrained_components
+    tokenizer = Tokenizers.from_pretrained("t5-small")
+    assert_instance_of Tokenizers::Normalizers::Precompiled, tokenizer.normalizer
+    assert_instance_of Tokenizers::PreTokenizers::Sequence, tokenizer.pre_tokenizer
+    assert_instance_of Tokenizers::Processors::TemplateProcessing, tokenizer.post_processor
+    assert_instance_of Tokenizers::Decoders::Metaspace, tokenizer.decoder
+  end
+
   def test_template_processing
     processor = Tokenizers::Processors::TemplateProcessing.new
     assert_instance_of Tokenizers::Processors::TemplateProcessing, processor
@@ -39,5 +101,42 @@ class ProcessorlTest < Minitest::Test
         ["[SEP]", 1]
       ]
     )
+
+    Tokenizers::Processors::TemplateProcessing.new(
+      single: ["[CLS]", "$A", "[SEP]"],
+      special_tokens: [
+        [0, "[CLS]"],
+        {id: "[SEP]", ids: [1], tokens: ["[SEP]"]}
+      ]
+    )
+  end
+
+  def test_template_processing_invalid
+    assert_raises(TypeError) do
+      Tokenizers::Processors::TemplateProcessing.new(single: 1)
+    end
+    assert_raises(ArgumentError) do
+      Tokenizers::Processors::TemplateProcessing.new(single: "[CLS] $Z")
+    end
+    assert_raises(TypeError) do
+      Tokenizers::Processors::TemplateProcessing.new(special_tokens: [1])
+    end
+    assert_raises(ArgumentError) do
+      Tokenizers::Processors::TemplateProcessing.new(special_tokens: [{id: "[SEP]", ids: [1, 2], tokens: ["[SEP]"]}])
+    end
+    assert_raises(ArgumentError) do
+      Tokenizers::Processors::TemplateProcessing.new(special_tokens: [{id: "[SEP]"}])
+    end
+    # missing special token
+    assert_raises(ArgumentError) do
+      Tokenizers::Processors::TemplateProcessing.new(single: "[CLS] $A")
+    end
+  end
+
+  def test_marshal
+    processor = Tokenizers::Processors::RobertaProcessing.new(["</s>", 2], ["<s>", 0])
+    copy = Marshal.load(Marshal.dump(processor))
+    assert_instance_of Tokenizers::Processors::RobertaProcessing, copy
+    assert_instance_of Tokenizers::Processors::RobertaProcessing, processor.dup
   end
 end
@@ -2,3 +2,6 @@ require "bundler/setup"
 Bundler.require(:default)
 require "minitest/autorun"
 require "minitest/pride"
+require "json"
+require "stringio"
+require "zlib"
@@ -93,6 +93,47 @@ class TokenizerTest < Minitest::Test
     assert_equal expected_offsets, encoded.offsets
   end
 
+  def test_offset_type
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    text = "😁 hello"
+
+    encoded = tokenizer.encode(text)
+    assert_equal [1, 7], encoded.offsets.last
+
+    encoded = tokenizer.encode(text, offset_type: :byte)
+    assert_equal [4, 10], encoded.offsets.last
+    assert_equal encoded.tokens.size - 1, encoded.char_to_token(5)
+
+    encoded = tokenizer.encode(text, offset_type: :utf16)
+    assert_equal "Ġhello", encoded.tokens.last
+    assert_equal [2, 8], encoded.offsets.last
+    assert_equal [2, 8], encoded.token_to_chars(encoded.tokens.size - 1)
+    assert_equal [2, 8], encoded.word_to_chars(1)
+    assert_equal encoded.tokens.size - 1, encoded.char_to_token(3)
+    assert_equal text.encode("UTF-16LE").bytesize / 2, encoded.offsets.last.last
+
+    encoded = tokenizer.encode_batch([text, "hello"], offset_type: :utf16)
+    assert_equal [2, 8], encoded[0].offsets.last
+    assert_equal [0, 5], encoded[1].offsets.last
+  end
+
+  def test_offset_type_pretokenized
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoded = tokenizer.encode(["naïve", "𝔘x"], is_pretokenized: true, add_special_tokens: false, offset_type: :utf16)
+    assert_equal [0, 5], encoded.word_to_chars(0)
+    assert_equal [0, 3], encoded.word_to_chars(1)
+
+    encoded = tokenizer.encode(["naïve", "𝔘x"], is_pretokenized: true, add_special_tokens: false, offset_type: :byte)
+    assert_equal [0, 6], encoded.word_to_chars(0)
+  end
+
+  def test_offset_type_invalid
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    assert_raises(ArgumentError) do
+      tokenizer.encode("hello", offset_type: :bad)
+    end
+  end
+
   def test_pair_encoding
     tokenizer = Tokenizers.from_pretrained("bert-base-cased")
     question = "Am I allowed to pass two text arguments?"
@@ -117,6 +158,237 @@ class TokenizerTest < Minitest::Test
     assert_equal encoded_wout_pretokenization.tokens, encoded_with_pretokenization.tokens
   end
 
+  def test_pretokenized_invalid
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    error = assert_raises(TypeError) do
+      tokenizer.encode("Hello", is_pretokenized: true)
+    end
+    assert_equal "PreTokenizedInputSequence must be an array of strings", error.message
+
+    assert_raises(TypeError) do
+      tokenizer.encode_batch([[["Hello"], 1]], is_pretokenized: true)
+    end
+    assert_raises(TypeError) do
+      tokenizer.encode_batch([["Hello", 1]])
+    end
+  end
+
+  def test_encode_large
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    encoded = tokenizer.encode("I can feel the magic, can you? " * 1000, add_special_tokens: false)
+    assert_equal 9000, encoded.ids.size
+  end
+
+  def test_count_tokens
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    text = "I can feel the magic, can you?"
+    assert_equal tokenizer.encode(text).ids.size, tokenizer.count_tokens(text)
+    assert_equal tokenizer.encode(text, add_special_tokens: false).ids.size, tokenizer.count_tokens(text, add_special_tokens: false)
+    assert_equal 2, tokenizer.count_tokens("")
+
+    long_text = text * 1000
+    assert_equal tokenizer.encode(long_text).ids.size, tokenizer.count_tokens(long_text)
+
+    # not affected by truncation or padding
+    count = tokenizer.count_tokens(text)
+    tokenizer.enable_truncation(4)
+    assert_equal count, tokenizer.count_tokens(text)
+    tokenizer.no_truncation
+    tokenizer.enable_padding(length: 32)
+    assert_equal count, tokenizer.count_tokens(text)
+  end
+
+  def test_count_tokens_added_tokens
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    text = "hello tokenizersrock"
+    count = tokenizer.count_tokens(text)
+    tokenizer.add_tokens(["tokenizersrock"])
+    assert_equal tokenizer.encode(text).ids.size, tokenizer.count_tokens(text)
+    assert_operator tokenizer.count_tokens(text), :<, count
+    tokenizer.encode_special_tokens = true
+    assert_equal tokenizer.encode("[SEP]").ids.size, tokenizer.count_tokens("[SEP]")
+  end
+
+  def test_count_tokens_batch
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    texts = ["Hello", "I can feel the magic, can you?"] * 500
+    expected = texts.map { |text| tokenizer.count_tokens(text) }
+    assert_equal expected, tokenizer.count_tokens_batch(texts)
+    assert_equal expected.map { |v| v - 2 }, tokenizer.count_tokens_batch(texts, add_special_tokens: false)
+    assert_equal [], tokenizer.count_tokens_batch([])
+  end
+
+  def test_chunk
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    text = "The naïve café served crème brûlée 😁 to everyone. It was delicious! Would you go back? Yes."
+
+    chunks = tokenizer.chunk(text, max_tokens: 8)
+    assert_operator chunks.size, :>, 1
+    assert_equal 0, chunks.first["offsets"][0]
+    assert_equal text.size, chunks.last["offsets"][1]
+    chunks.each do |chunk|
+      assert_operator chunk["token_count"], :<=, 8
+      assert_equal text[chunk["offsets"][0]...chunk["offsets"][1]], chunk["text"]
+      assert_equal tokenizer.count_tokens(chunk["text"], add_special_tokens: false), chunk["token_count"]
+    end
+    chunks.each_cons(2) do |a, b|
+      assert_operator b["offsets"][0], :>=, a["offsets"][1]
+      # never splits inside a word
+      refute_match(/[[:alnum:]]{2}/, text[a["offsets"][1] - 1, 2])
+    end
+
+    assert_equal [], tokenizer.chunk("", max_tokens: 8)
+    assert_equal [{"text" => "Hello", "offsets" => [0, 5], "token_count" => 1}], tokenizer.chunk("Hello", max_tokens: 8)
+  end
+
+  def test_chunk_long_word
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    word = "supercalifragilisticexpialidocious"
+
+    chunks = tokenizer.chunk("a #{word} b", max_tokens: 2)
+    assert_equal ["a", word, "b"], chunks.map { |c| c["text"] }
+    assert_equal tokenizer.count_tokens(word, add_special_tokens: false), chunks[1]["token_count"]
+    assert_operator chunks[1]["token_count"], :>, 2
+  end
+
+  def test_chunk_overlap
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    text = "one two three four five six seven eight nine ten"
+
+    chunks = tokenizer.chunk(text, max_tokens: 4, overlap: 2)
+    assert_equal ["one two three four", "three four five six", "five six seven eight", "seven eight nine ten"], chunks.map { |c| c["text"] }
+    assert_equal [0, 18], chunks[0]["offsets"]
+  end
+
+  def test_chunk_sentence
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    text = "I can feel the magic. Can you? Hello world. This sentence is much too long to fit in a single chunk."
+
+    chunks = tokenizer.chunk(text, max_tokens: 10, respect: :sentence)
+    assert_equal ["I can feel the magic. Can you?", "Hello world."], chunks.first(2).map { |c| c["text"] }
+    assert chunks.drop(2).all? { |c| c["token_count"] <= 10 }
+    assert_equal "chunk.", chunks.last["text"].split.last
+  end
+
+  def test_chunk_invalid
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    assert_raises(ArgumentError) do
+      tokenizer.chunk("Hello", max_tokens: 0)
+    end
+    assert_raises(ArgumentError) do
+      tokenizer.chunk("Hello", max_tokens: 4, overlap: 4)
+    end
+    assert_raises(ArgumentError) do
+      tokenizer.chunk("Hello", max_tokens: 4, respect: :paragraph)
+    end
+  end
+
+  def test_encode_batch_padding
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.enable_padding
+    encodings = tokenizer.encode_batch(["Short"] * 1000 + ["I can feel the magic, can you?"])
+    assert_equal [11], encodings.map { |e| e.ids.size }.uniq
+  end
+
+  def test_encode_batch_threads
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    texts = ["I can feel the magic, can you?"] * 1000
+    expected = tokenizer.encode_batch(texts).map(&:ids)
+    threads = 4.times.map { Thread.new { tokenizer.encode_batch(texts).map(&:ids) } }
+    threads.each do |thread|
+      assert_equal expected, thread.value
+    end
+  end
+
+  def test_encode_batch_threads_configure
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    texts = ["I can feel the magic, can you?"] * 1000
+    threads = [
+      Thread.new { 10.times { tokenizer.encode_batch(texts) } },
+      Thread.new { 10.times { tokenizer.enable_padding; tokenizer.no_padding } }
+    ]
+    threads.each(&:join)
+  end
+
+  def test_components
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    assert_instance_of Tokenizers::Models::WordPiece, tokenizer.model
+    assert_instance_of Tokenizers::Normalizers::BertNormalizer, tokenizer.normalizer
+    assert_instance_of Tokenizers::PreTokenizers::BertPreTokenizer, tokenizer.pre_tokenizer
+    assert_instance_of Tokenizers::Processors::TemplateProcessing, tokenizer.post_processor
+    assert_instance_of Tokenizers::Decoders::WordPiece, tokenizer.decoder
+
+    tokenizer = Tokenizers::Tokenizer.new(Tokenizers::Models::BPE.new)
+    assert_nil tokenizer.normalizer
+    assert_nil tokenizer.pre_tokenizer
+    assert_nil tokenizer.post_processor
+    assert_nil tokenizer.decoder
+  end
+
+  def test_components_shared
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    pre_tokenizer = tokenizer.pre_tokenizer
+    assert_instance_of Tokenizers::PreTokenizers::ByteLevel, pre_tokenizer
+    assert_equal false, pre_tokenizer.add_prefix_space
+
+    pre_tokenizer.add_prefix_space = true
+    assert_equal true, tokenizer.pre_tokenizer.add_prefix_space
+    assert_equal "ĠMyth", tokenizer.encode("Myth").tokens.first
+  end
+
+  def test_components_frozen
+    tokenizer = Tokenizers.from_pretrained("gpt2").freeze
+    pre_tokenizer = tokenizer.pre_tokenizer
+    pre_tokenizer.add_prefix_space = true
+    assert_equal false, tokenizer.pre_tokenizer.add_prefix_space
+    refute_equal "ĠMyth", tokenizer.encode("Myth").tokens.first
+
+    model = tokenizer.model
+    model.dropout = 0.5
+    assert_nil tokenizer.model.dropout
+  end
+
+  def test_components_before_freeze
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    pre_tokenizer = tokenizer.pre_tokenizer
+    model = tokenizer.model
+    tokenizer.freeze
+
+    pre_tokenizer.add_prefix_space = true
+    model.dropout = 0.5
+    assert_equal false, tokenizer.pre_tokenizer.add_prefix_space
+    assert_nil tokenizer.model.dropout
+    refute_equal "ĠMyth", tokenizer.encode("Myth").tokens.first
+
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    normalizer = Tokenizers::Normalizers::BertNormalizer.new(lowercase: false)
+    tokenizer.normalizer = normalizer
+    Ractor.make_shareable(tokenizer)
+
+    normalizer.lowercase = true
+    assert_equal false, tokenizer.normalizer.lowercase
+    assert_equal "Hello", tokenizer.encode("Hello", add_special_tokens: false).tokens.first
+  end
+
+  def test_freeze
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased").freeze
+    assert_raises(FrozenError) { tokenizer.enable_padding }
+    assert_raises(FrozenError) { tokenizer.add_tokens(["mellifluous"]) }
+    assert_raises(FrozenError) { tokenizer.pre_tokenizer = Tokenizers::PreTokenizers::Whitespace.new }
+
+    expected_ids = [101, 146, 1169, 1631, 1103, 3974, 117, 1169, 1128, 136, 102]
+    assert_equal expected_ids, tokenizer.encode("I can feel the magic, can you?").ids
+  end
+
+  def test_ractor
+    tokenizer = Ractor.make_shareable(Tokenizers.from_pretrained("bert-base-cased"))
+    assert tokenizer.frozen?
+
+    ractor = Ractor.new(tokenizer) { |t| t.encode("I can feel the magic, can you?").ids }
+    expected_ids = [101, 146, 1169, 1631, 1103, 3974, 117, 1169, 1128, 136, 102]
+    assert_equal expected_ids, ractor.take
+  end
+
   def test_decode_with_special_tokens
     tokenizer = Tokenizers.from_pretrained("bert-base-cased")
 
@@ -165,6 +437,149 @@ class TokenizerTest < Minitest::Test
     assert_equal 28996, vocab_with_added_tokens["mellifluous"]
   end
 
+  def test_added_tokens_decoder
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    decoder = tokenizer.added_tokens_decoder
+    assert_equal [0, 100, 101, 102, 103], decoder.keys
+    assert_instance_of Tokenizers::AddedToken, decoder[101]
+    assert_equal "[CLS]", decoder[101].content
+    assert_equal true, decoder[101].special
+
+    tokenizer.add_tokens(["mellifluous"])
+    assert_equal false, tokenizer.added_tokens_decoder[28996].special
+  end
+
+  def test_special_tokens
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.add_tokens(["mellifluous"])
+    assert_equal ["[PAD]", "[UNK]", "[CLS]", "[SEP]", "[MASK]"], tokenizer.special_tokens
+    assert_equal [0, 100, 101, 102, 103], tokenizer.special_token_ids
+  end
+
+  def test_remove_added_tokens
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.add_tokens(["mellifluous", "malodorous"])
+    tokenizer.enable_padding(length: 8)
+
+    assert_equal 1, tokenizer.remove_added_tokens(["malodorous", "missing"])
+    assert_nil tokenizer.vocab["malodorous"]
+    assert_equal 28996, tokenizer.token_to_id("mellifluous")
+    assert_equal 28997, tokenizer.vocab_size
+    assert_equal 8, tokenizer.encode("mellifluous").ids.size
+    assert_equal 0, tokenizer.remove_added_tokens(["missing"])
+  end
+
+  def test_remove_added_tokens_ids
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.add_tokens(["mellifluous", "malodorous", "mendacious"])
+
+    error = assert_raises(ArgumentError) do
+      tokenizer.remove_added_tokens(["malodorous"])
+    end
+    assert_equal "Cannot remove added tokens without changing the ids of other added tokens", error.message
+    assert_equal 28996, tokenizer.token_to_id("mellifluous")
+    assert_equal 28997, tokenizer.token_to_id("malodorous")
+    assert_equal 28998, tokenizer.token_to_id("mendacious")
+
+    assert_equal 2, tokenizer.remove_added_tokens(["malodorous", "mendacious"])
+    assert_equal 28996, tokenizer.token_to_id("mellifluous")
+    assert_nil tokenizer.token_to_id("malodorous")
+  end
+
+  def test_rename_added_token
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.add_tokens(["mellifluous"])
+
+    tokenizer.rename_added_token("mellifluous", "malodorous")
+    assert_nil tokenizer.token_to_id("mellifluous")
+    assert_equal 28996, tokenizer.token_to_id("malodorous")
+    assert_equal ["malodorous"], tokenizer.encode("malodorous", add_special_tokens: false).tokens
+
+    error = assert_raises(ArgumentError) do
+      tokenizer.rename_added_token("missing", "other")
+    end
+    assert_equal "Added token not found: missing", error.message
+
+    assert_raises(ArgumentError) do
+      tokenizer.rename_added_token("malodorous", "[CLS]")
+    end
+
+    # would use the id from the model
+    assert_raises(ArgumentError) do
+      tokenizer.rename_added_token("malodorous", "hello")
+    end
+    assert_equal 28996, tokenizer.token_to_id("malodorous")
+  end
+
+  def test_encode_special_tokens
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    assert_equal false, tokenizer.encode_special_tokens
+    assert_equal [50256], tokenizer.encode("<|endoftext|>").ids
+
+    assert_equal [27, 91, 437, 1659, 5239, 91, 29], tokenizer.encode("<|endoftext|>", encode_special_tokens: true).ids
+
+    tokenizer.encode_special_tokens = true
+    assert_equal true, tokenizer.encode_special_tokens
+    assert_equal [27, 91, 437, 1659, 5239, 91, 29], tokenizer.encode("<|endoftext|>").ids
+    assert_equal [50256], tokenizer.encode("<|endoftext|>", encode_special_tokens: false).ids
+  end
+
+  def test_allowed_special
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokens = tokenizer.encode("[CLS] [MASK]", add_special_tokens: false, allowed_special: ["[MASK]"]).tokens
+    assert_equal "[", tokens.first
+    assert_equal "[MASK]", tokens.last
+    refute_includes tokens, "[CLS]"
+
+    tokens = tokenizer.encode("[CLS] [MASK]", add_special_tokens: false, allowed_special: :all).tokens
+    assert_equal ["[CLS]", "[MASK]"], tokens
+
+    tokens = tokenizer.encode("[CLS] [MASK]", add_special_tokens: false, allowed_special: []).tokens
+    refute_includes tokens, "[CLS]"
+    refute_includes tokens, "[MASK]"
+
+    # does not change the tokenizer
+    assert_equal ["[CLS]", "[MASK]"], tokenizer.encode("[CLS] [MASK]", add_special_tokens: false).tokens
+    assert_equal 103, tokenizer.token_to_id("[MASK]")
+
+    # uses tokens added after the first encode
+    tokenizer.add_special_tokens(["[NEW]"])
+    tokens = tokenizer.encode("[CLS] [NEW]", add_special_tokens: false, allowed_special: :all).tokens
+    assert_equal ["[CLS]", "[NEW]"], tokens
+  end
+
+  def test_disallowed_special
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    error = assert_raises(Tokenizers::DisallowedSpecialTokenError) do
+      tokenizer.encode("hello <|endoftext|>", allowed_special: [], disallowed_special: :all)
+    end
+    assert_equal "Encountered disallowed special token: <|endoftext|>", error.message
+    assert_equal "<|endoftext|>", error.token
+    assert_equal 50256, error.token_id
+    assert_kind_of Tokenizers::Error, error
+
+    assert_raises(Tokenizers::DisallowedSpecialTokenError) do
+      tokenizer.encode_batch(["hello", "<|endoftext|>"], encode_special_tokens: true, disallowed_special: ["<|endoftext|>"])
+    end
+
+    # no tokens are allowed by default
+    assert_raises(Tokenizers::DisallowedSpecialTokenError) do
+      tokenizer.encode("<|endoftext|>", disallowed_special: :all)
+    end
+    assert_equal [50256], tokenizer.encode("<|endoftext|>", allowed_special: :all, disallowed_special: :all).ids
+    assert_equal [31373], tokenizer.encode("hello", allowed_special: [], disallowed_special: :all).ids
+  end
+
+  def test_special_tokens_policy_invalid
+    tokenizer = Tokenizers.from_pretrained("gpt2")
+    assert_raises(ArgumentError) do
+      tokenizer.encode("hello", allowed_special: :some)
+    end
+    assert_raises(ArgumentError) do
+      tokenizer.encode("hello", encode_special_tokens: true, allowed_special: [])
+    end
+  end
+
   def test_padding
     tokenizer = Tokenizers.from_pretrained("bert-base-cased")
     assert_nil tokenizer.padding
@@ -219,9 +634,255 @@ class TokenizerTest < Minitest::Test
     assert_equal 28996, new_tokenizer.vocab["mellifluous"]
   end
 
+  def test_from_str
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    new_tokenizer = Tokenizers::Tokenizer.from_str(tokenizer.to_s)
+    assert_equal tokenizer.vocab_size, new_tokenizer.vocab_size
+  end
+
+  def test_from_str_invalid
+    error = assert_raises(Tokenizers::SerializationError) do
+      Tokenizers::Tokenizer.from_str("{\n  \"version\": ")
+    end
+    assert_match "line 2 column", error.message
+    assert_kind_of Tokenizers::Error, error
+  end
+
+  def test_from_file_missing
+    error = assert_raises(Tokenizers::IOError) do
+      Tokenizers.from_file("missing.json")
+    end
+    assert_equal "missing.json", error.path
+    assert_kind_of Tokenizers::Error, error
+  end
+
+  def test_train_missing_file
+    tokenizer = Tokenizers::Tokenizer.new(Tokenizers::Models::BPE.new)
+    assert_raises(Tokenizers::IOError) do
+      tokenizer.train(["missing.txt"], Tokenizers::Trainers::BpeTrainer.new)
+    end
+  end
+
+  def test_from_buffer
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    new_tokenizer = Tokenizers::Tokenizer.from_buffer(tokenizer.to_s.b)
+    assert_equal tokenizer.vocab_size, new_tokenizer.vocab_size
+  end
+
+  def test_io
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    io = StringIO.new
+    tokenizer.save(io)
+    io.rewind
+    new_tokenizer = Tokenizers::Tokenizer.from_io(io)
+    assert_equal tokenizer.vocab_size, new_tokenizer.vocab_size
+  end
+
+  def test_compressed
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+
+    %w(gz zst).each do |ext|
+      path = "/tmp/tokenizer.json.#{ext}"
+      tokenizer.save(path)
+      refute File.binread(path).start_with?("{")
+
+      new_tokenizer = Tokenizers.from_file(path)
+      assert_equal tokenizer.vocab_size, new_tokenizer.vocab_size
+
+      new_tokenizer = Tokenizers::Tokenizer.from_buffer(File.binread(path))
+      assert_equal tokenizer.vocab_size, new_tokenizer.vocab_size
+    end
+
+    path = "/tmp/tokenizer-zlib.json.gz"
+    Zlib::GzipWriter.open(path) { |gz| gz.write(tokenizer.to_s) }
+    new_tokenizer = Tokenizers.from_file(path)
+    assert_equal tokenizer.vocab_size, new_tokenizer.vocab_size
+  end
+
+  def test_train_from_iterator
+    tokenizer = Tokenizers::Tokenizer.new(Tokenizers::Models::BPE.new(unk_token: "[UNK]"))
+    tokenizer.pre_tokenizer = Tokenizers::PreTokenizers::Whitespace.new
+    trainer = Tokenizers::Trainers::BpeTrainer.new(special_tokens: ["[UNK]"], show_progress: false)
+
+    data = ["hello world", ["hello there", "world peace"]] * 100
+    tokenizer.train_from_iterator(data, trainer: trainer, length: data.size)
+    assert tokenizer.vocab_size > 1
+    assert_equal ["hello", "world"], tokenizer.encode("hello world").tokens
+
+    tokenizer = Tokenizers::Tokenizer.new(Tokenizers::Models::BPE.new(unk_token: "[UNK]"))
+    tokenizer.train_from_iterator(data.each.lazy.map { |v| v }, trainer: trainer)
+    assert tokenizer.vocab_size > 1
+  end
+
+  def test_train_from_iterator_invalid
+    tokenizer = Tokenizers::Tokenizer.new(Tokenizers::Models::BPE.new)
+    assert_raises(TypeError) do
+      tokenizer.train_from_iterator([1, 2, 3])
+    end
+    assert_equal({}, tokenizer.vocab)
+
+    # fails after the first batch
+    assert_raises(TypeError) do
+      tokenizer.train_from_iterator((["hello world"] * 1000) + [1])
+    end
+    assert_equal({}, tokenizer.vocab)
+
+    enum = Enumerator.new do |y|
+      1000.times { y << "hello world" }
+      raise ArgumentError, "bad data"
+    end
+    assert_raises(ArgumentError) do
+      tokenizer.train_from_iterator(enum)
+    end
+    assert_equal({}, tokenizer.vocab)
+  end
+
+  class CustomNormalizer
+    def normalize(normalized)
+      normalized.lowercase
+    end
+  end
+
+  def test_custom_normalizer
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.normalizer = Tokenizers::Normalizers::Custom.new(CustomNormalizer.new)
+    assert_instance_of Tokenizers::Normalizers::Custom, tokenizer.normalizer
+
+    expected_tokens = ["[CLS]", "i", "can", "feel", "the", "magic", ",", "can", "you", "?", "[SEP]"]
+    assert_equal expected_tokens, tokenizer.encode("I CAN FEEL the magic, can you?").tokens
+    assert_equal [expected_tokens] * 2, tokenizer.encode_batch(["I CAN FEEL the magic, can you?"] * 2).map(&:tokens)
+    assert_equal 11, tokenizer.count_tokens("I CAN FEEL the magic, can you?")
+    assert_equal [11, 11], tokenizer.count_tokens_batch(["I CAN FEEL the magic, can you?"] * 2)
+
+    error = assert_raises(Tokenizers::SerializationError) do
+      tokenizer.to_s
+    end
+    assert_equal "Custom Normalizer cannot be serialized", error.message
+  end
+
+  def test_custom_normalizer_error
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    normalizer = Object.new
+    def normalizer.normalize(normalized)
+      raise ArgumentError, "bad input"
+    end
+    tokenizer.normalizer = Tokenizers::Normalizers::Custom.new(normalizer)
+
+    assert_raises(ArgumentError) { tokenizer.encode("hello") }
+    assert_raises(ArgumentError) { tokenizer.encode_batch(["hello"]) }
+  end
+
+  def test_custom_normalizer_error_once
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    calls = 0
+    normalizer = Object.new
+    normalizer.define_singleton_method(:normalize) do |normalized|
+      calls += 1
+      raise ArgumentError, "bad input" if calls == 1
+      normalized.lowercase
+    end
+    tokenizer.normalizer = Tokenizers::Normalizers::Custom.new(normalizer)
+
+    assert_raises(ArgumentError) { tokenizer.encode("HELLO") }
+    GC.start
+    assert_equal ["[CLS]", "hello", "[SEP]"], tokenizer.encode("HELLO").tokens
+    assert_equal 3, tokenizer.count_tokens("HELLO")
+  end
+
+  def test_custom_normalizer_train
+    tokenizer = Tokenizers::Tokenizer.new(Tokenizers::Models::BPE.new)
+    tokenizer.normalizer = Tokenizers::Normalizers::Custom.new(CustomNormalizer.new)
+    error = assert_raises(Tokenizers::TrainingError) do
+      tokenizer.train_from_iterator(["hello world"])
+    end
+    assert_equal "Training is not supported with custom components", error.message
+  end
+
+  def test_custom_normalizer_ractor
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.normalizer = Tokenizers::Normalizers::Custom.new(CustomNormalizer.new)
+    Ractor.make_shareable(tokenizer)
+
+    ractor = Ractor.new(tokenizer) { |t| t.encode("hello").ids }
+    error = assert_raises(Ractor::RemoteError) { ractor.take }
+    assert_kind_of Tokenizers::Error, error.cause
+  end
+
+  class CustomPreTokenizer
+    def pre_tokenize(pretok)
+      pretok.split { |_i, normalized| normalized.split(" ", "removed") }
+      pretok.tokenize { |s| [[s.length, s, [0, s.length]]] }
+    end
+  end
+
+  def test_custom_pre_tokenizer
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.pre_tokenizer = Tokenizers::PreTokenizers::Custom.new(CustomPreTokenizer.new)
+    assert_instance_of Tokenizers::PreTokenizers::Custom, tokenizer.pre_tokenizer
+
+    encoding = tokenizer.encode("the magic")
+    assert_equal ["[CLS]", "the", "magic", "[SEP]"], encoding.tokens
+    assert_equal [101, 3, 5, 102], encoding.ids
+    assert_equal [[0, 0], [0, 3], [4, 9], [0, 0]], encoding.offsets
+
+    error = assert_raises(Tokenizers::SerializationError) do
+      tokenizer.to_s
+    end
+    assert_equal "Custom PreTokenizer cannot be serialized", error.message
+  end
+
+  def test_custom_pre_tokenizer_reentrant
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    pre_tokenizer = Object.new
+    pre_tokenizer.define_singleton_method(:pre_tokenize) do |_pretok|
+      tokenizer.enable_padding
+    end
+    tokenizer.pre_tokenizer = Tokenizers::PreTokenizers::Custom.new(pre_tokenizer)
+
+    error = assert_raises(Tokenizers::Error) do
+      tokenizer.encode("hello")
+    end
+    assert_equal "Cannot use a tokenizer from a callback while it's in use", error.message
+    assert_nil tokenizer.padding
+  end
+
   def test_num_special_tokens_to_add
     tokenizer = Tokenizers.from_pretrained("bert-base-cased")
     assert_equal 3, tokenizer.num_special_tokens_to_add(true)
     assert_equal 2, tokenizer.num_special_tokens_to_add(false)
   end
+
+  def test_dup
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.chat_template = "{{ messages[0]['content'] }}"
+
+    copy = tokenizer.dup
+    copy.enable_padding(length: 16)
+    copy.model.unk_token = "[PAD]"
+    assert_nil tokenizer.padding
+    assert_equal "[UNK]", tokenizer.model.unk_token
+    assert_equal 16, copy.padding["length"]
+    assert_equal tokenizer.chat_template, copy.chat_template
+
+    tokenizer.freeze
+    assert tokenizer.clone.frozen?
+    refute tokenizer.clone(freeze: false).frozen?
+    refute tokenizer.dup.frozen?
+  end
+
+  def test_marshal
+    tokenizer = Tokenizers.from_pretrained("bert-base-cased")
+    tokenizer.enable_truncation(8)
+    tokenizer.encode_special_tokens = true
+    tokenizer.chat_template = Tokenizers::ChatTemplate.new("{{ bos_token }}{{ messages[0]['content'] }}", bos_token: "[CLS]")
+
+    copy = Marshal.load(Marshal.dump(tokenizer))
+    assert_instance_of Tokenizers::Tokenizer, copy
+    assert_equal tokenizer.to_s, copy.to_s
+    assert_equal tokenizer.truncation, copy.truncation
+    assert_equal true, copy.encode_special_tokens
+    assert_equal "[CLS]", copy.chat_template.bos_token
+    text = "I can feel the magic, can you? [SEP]"
+    assert_equal tokenizer.encode(text).ids, copy.encode(text).ids
+  end
 end
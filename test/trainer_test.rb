@@ -156,4 +156,26 @@ class TrainerTest < Minitest::Test
     trainer.end_of_word_suffix = "#x#"
     assert_equal "#x#", trainer.end_of_word_suffix
   end
+
+  def test_unknown_keyword
+    error = assert_raises(Tokenizers::UnknownKeywordError) do
+      Tokenizers::Trainers::BpeTrainer.new(vocab: 100)
+    end
+    assert_equal [:vocab], error.keywords
+  end
+
+  def test_dup
+    trainer = Tokenizers::Trainers::BpeTrainer.new(vocab_size: 1000)
+    copy = trainer.dup
+    copy.vocab_size = 2000
+    assert_equal 1000, trainer.vocab_size
+    assert_equal 2000, copy.vocab_size
+  end
+
+  def test_marshal
+    trainer = Tokenizers::Trainers::WordPieceTrainer.new(vocab_size: 1000, special_tokens: ["[UNK]"])
+    copy = Marshal.load(Marshal.dump(trainer))
+    assert_instance_of Tokenizers::Trainers::WordPieceTrainer, copy
+    assert_equal 1000, copy.vocab_size
+  end
 end